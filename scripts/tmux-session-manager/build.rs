@@ -8,7 +8,7 @@ fn main() {
 
     // Get git commit hash for version info
     if let Ok(output) = Command::new("git")
-        .args(&["rev-parse", "--short", "HEAD"])
+        .args(["rev-parse", "--short", "HEAD"])
         .output()
     {
         if output.status.success() {
@@ -1,13 +1,85 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 
+use crate::core::template::SessionTemplate;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub preview_enabled: bool,
     pub plugin_dir: Option<PathBuf>,
     pub tmux_socket: Option<String>,
     pub ui_settings: UiSettings,
+    /// Sessions listed first in the picker
+    #[serde(default)]
+    pub pinned_sessions: Vec<String>,
+    /// User-defined tags per session name
+    #[serde(default)]
+    pub session_tags: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    pub prune: PruneSettings,
+    /// Glob patterns of session names that cannot be killed without forcing
+    #[serde(default)]
+    pub protected_sessions: Vec<String>,
+    /// Directories whose children are offered when creating a session (`~` is expanded)
+    #[serde(default = "default_project_roots")]
+    pub project_roots: Vec<String>,
+    /// Window layouts offered when creating a session
+    #[serde(default)]
+    pub templates: Vec<SessionTemplate>,
+    /// Projects always offered in the picker, started on selection
+    #[serde(default)]
+    pub projects: Vec<ProjectEntry>,
+    #[serde(default)]
+    pub repo_scan: RepoScanSettings,
+    #[serde(default)]
+    pub worktrees: WorktreeSettings,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PruneSettings {
+    /// Sessions not attached for longer than this are offered for pruning
+    pub idle_threshold_hours: i64,
+    /// Sessions carrying any of these tags are never pruned (pinned sessions never are either)
+    pub whitelist_tags: Vec<String>,
+}
+
+/// Where the repo plugin looks for git repositories
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RepoScanSettings {
+    /// Directories to scan (`~` is expanded); `project_roots` when unset, and off when empty
+    pub roots: Option<Vec<String>>,
+    /// How many levels below each root to look
+    pub max_depth: usize,
+    /// Glob patterns of directory names never descended into
+    pub ignore: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorktreeSettings {
+    /// Repositories whose worktrees are always listed, besides those of running sessions (`~` is expanded)
+    pub repos: Vec<String>,
+    /// Session name for a worktree, from `{repo}`, `{branch}` (the worktree name when detached) and `{worktree}`
+    pub naming: String,
+    /// Where new worktrees are created, from `{repo}` and `{branch}`; relative to the main checkout's parent
+    pub location: String,
+}
+
+/// A project configured under `projects`, listed whether or not its session is running
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectEntry {
+    pub name: String,
+    /// Directory the session starts in (`~` is expanded)
+    pub path: String,
+    /// One of the configured `templates`, applied when the session is created
+    #[serde(default)]
+    pub template: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +97,48 @@ impl Default for Config {
             plugin_dir: None, // Will use default ~/.config/tmux-session-manager/plugins
             tmux_socket: None,
             ui_settings: UiSettings::default(),
+            pinned_sessions: Vec::new(),
+            session_tags: HashMap::new(),
+            prune: PruneSettings::default(),
+            protected_sessions: Vec::new(),
+            project_roots: default_project_roots(),
+            templates: Vec::new(),
+            projects: Vec::new(),
+            repo_scan: RepoScanSettings::default(),
+            worktrees: WorktreeSettings::default(),
+        }
+    }
+}
+
+fn default_project_roots() -> Vec<String> {
+    vec!["~/projects".to_string(), "~/src".to_string(), "~/code".to_string()]
+}
+
+impl Default for PruneSettings {
+    fn default() -> Self {
+        Self {
+            idle_threshold_hours: 72,
+            whitelist_tags: vec!["keep".to_string()],
+        }
+    }
+}
+
+impl Default for RepoScanSettings {
+    fn default() -> Self {
+        Self {
+            roots: None,
+            max_depth: 3,
+            ignore: vec!["node_modules".to_string(), "target".to_string(), "vendor".to_string()],
+        }
+    }
+}
+
+impl Default for WorktreeSettings {
+    fn default() -> Self {
+        Self {
+            repos: Vec::new(),
+            naming: "{repo}/{branch}".to_string(),
+            location: "{repo}-{branch}".to_string(),
         }
     }
 }
@@ -84,9 +198,94 @@ impl Config {
         }
     }
 
+    /// Directories of configured projects and worktree repositories, unexpanded
+    pub fn known_project_dirs(&self) -> Vec<String> {
+        self.projects
+            .iter()
+            .map(|project| project.path.clone())
+            .chain(self.worktrees.repos.iter().cloned())
+            .collect()
+    }
+
+    /// Repo scan settings with the roots resolved, falling back to `project_roots`
+    pub fn repo_scan_settings(&self) -> RepoScanSettings {
+        RepoScanSettings {
+            roots: Some(self.repo_scan.roots.clone().unwrap_or_else(|| self.project_roots.clone())),
+            ..self.repo_scan.clone()
+        }
+    }
+
     pub async fn toggle_preview(&mut self) -> Result<()> {
         self.preview_enabled = !self.preview_enabled;
         self.save().await
     }
 
-}
\ No newline at end of file
+    pub fn is_pinned(&self, session_name: &str) -> bool {
+        self.pinned_sessions.iter().any(|name| name == session_name)
+    }
+
+    pub fn tags_for(&self, session_name: &str) -> Vec<String> {
+        self.session_tags.get(session_name).cloned().unwrap_or_default()
+    }
+
+    /// Pin every session unless all of them are already pinned, in which case unpin them
+    pub async fn toggle_pins(&mut self, session_names: &[String]) -> Result<()> {
+        if session_names.iter().all(|name| self.is_pinned(name)) {
+            self.pinned_sessions.retain(|name| !session_names.contains(name));
+        } else {
+            for name in session_names {
+                if !self.is_pinned(name) {
+                    self.pinned_sessions.push(name.clone());
+                }
+            }
+        }
+        self.save().await
+    }
+
+    /// Add a tag to every session unless all of them already carry it, in which case remove it
+    pub async fn toggle_tag(&mut self, session_names: &[String], tag: &str) -> Result<()> {
+        let all_tagged = session_names
+            .iter()
+            .all(|name| self.tags_for(name).iter().any(|t| t == tag));
+
+        for name in session_names {
+            let tags = self.session_tags.entry(name.clone()).or_default();
+            if all_tagged {
+                tags.retain(|t| t != tag);
+            } else if !tags.iter().any(|t| t == tag) {
+                tags.push(tag.to_string());
+            }
+        }
+        self.session_tags.retain(|_, tags| !tags.is_empty());
+
+        self.save().await
+    }
+
+    /// Carry pins and tags over to a session's new name
+    pub async fn rename_session(&mut self, old_name: &str, new_name: &str) -> Result<()> {
+        let pinned = self.is_pinned(old_name);
+        let tags = self.session_tags.remove(old_name);
+        if !pinned && tags.is_none() {
+            return Ok(());
+        }
+
+        for name in self.pinned_sessions.iter_mut().filter(|name| *name == old_name) {
+            *name = new_name.to_string();
+        }
+        if let Some(tags) = tags {
+            self.session_tags.insert(new_name.to_string(), tags);
+        }
+
+        self.save().await
+    }
+
+}
+
+/// Whether `name` matches any of the glob `patterns`; invalid patterns never match
+pub fn matches_any_pattern(patterns: &[String], name: &str) -> bool {
+    patterns.iter().any(|pattern| {
+        glob::Pattern::new(pattern)
+            .map(|pattern| pattern.matches(name))
+            .unwrap_or(false)
+    })
+}
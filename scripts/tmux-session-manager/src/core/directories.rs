@@ -0,0 +1,245 @@
+use anyhow::Result;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+use crate::core::session::{SessionContext, SessionStamp, TmuxSession};
+use crate::core::tmux::TmuxClient;
+use crate::core::{git, naming};
+
+/// Expand a leading `~` to the home directory
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix('~'), dirs::home_dir()) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+            home.join(rest.trim_start_matches('/'))
+        }
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Shorten paths under the home directory to `~/...` for display
+pub fn abbreviate_home(path: &Path) -> String {
+    match dirs::home_dir().and_then(|home| path.strip_prefix(home).ok().map(Path::to_path_buf)) {
+        Some(rest) if rest.as_os_str().is_empty() => "~".to_string(),
+        Some(rest) => format!("~/{}", rest.display()),
+        None => path.display().to_string(),
+    }
+}
+
+/// Directories offered when creating a session, most relevant first: the
+/// current directory, children of each project root (plus git repos one level
+/// deeper, e.g. `~/src/org/repo`), then zoxide history
+pub async fn candidate_directories(project_roots: &[String]) -> Vec<PathBuf> {
+    let mut candidates = Vec::new();
+
+    if let Ok(current) = std::env::current_dir() {
+        candidates.push(current);
+    }
+
+    for root in project_roots {
+        for child in subdirectories(&expand_home(root)).await {
+            let is_repo = git::is_repo_root(&child);
+            candidates.push(child.clone());
+
+            if !is_repo {
+                let nested = subdirectories(&child).await;
+                candidates.extend(nested.into_iter().filter(|dir| git::is_repo_root(dir)));
+            }
+        }
+    }
+
+    candidates.extend(zoxide_directories().await);
+
+    let mut seen = HashSet::new();
+    candidates
+        .into_iter()
+        .filter(|dir| dir.is_dir() && seen.insert(dir.clone()))
+        .collect()
+}
+
+/// Session name for a directory: the repository name from its `origin`
+/// remote, falling back to the directory name
+pub async fn derive_session_name(dir: &Path) -> String {
+    let remote_name = match git::remote_url(dir, "origin").await {
+        Some(url) => git::repo_name_from_url(&url),
+        None => None,
+    };
+
+    let name = remote_name.unwrap_or_else(|| {
+        dir.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
+    });
+
+    naming::sanitize(&name)
+}
+
+/// Project directory for a path: the repository root when inside one, else the path itself
+pub async fn project_root(path: &Path) -> PathBuf {
+    git::repo_root(path).await.unwrap_or_else(|| path.to_path_buf())
+}
+
+/// The running session for a directory, matching the recorded `@tsm_path`
+/// before falling back to the directory its active pane is in
+pub fn find_session_for_dir<'a>(dir: &Path, sessions: &'a [TmuxSession]) -> Option<&'a TmuxSession> {
+    let matches = |path: Option<&String>| path.is_some_and(|path| Path::new(path) == dir);
+
+    sessions
+        .iter()
+        .find(|session| matches(session.options.path.as_ref()))
+        .or_else(|| sessions.iter().find(|session| matches(session.current_path.as_ref())))
+}
+
+/// Name of the session for `path`'s project, creating it at the project root when none is running
+pub async fn ensure_session_for_path(tmux: &TmuxClient, path: &Path, context: &SessionContext) -> Result<String> {
+    let root = project_root(path).await;
+    let sessions = &context.all_tmux_sessions;
+
+    if let Some(session) = find_session_for_dir(&root, sessions).or_else(|| find_session_for_dir(path, sessions)) {
+        return Ok(session.name.clone());
+    }
+
+    let derived_name = derive_session_name(&root).await;
+    let session_name = naming::unique_name(&derived_name, |candidate| {
+        sessions.iter().any(|session| session.name == candidate)
+    });
+
+    // Plain sessions are owned by the active plugin
+    let root = root.to_string_lossy();
+    tmux.new_session(&session_name, Some(&root)).await?;
+    tmux.stamp_session(&session_name, &SessionStamp::new("active").with_path(&root)).await?;
+
+    Ok(session_name)
+}
+
+/// Visible subdirectories, sorted by name; missing or unreadable directories yield nothing
+async fn subdirectories(dir: &Path) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    let Ok(mut entries) = tokio::fs::read_dir(dir).await else {
+        return dirs;
+    };
+
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let hidden = entry.file_name().to_string_lossy().starts_with('.');
+        if !hidden && entry.file_type().await.map(|kind| kind.is_dir()).unwrap_or(false) {
+            dirs.push(entry.path());
+        }
+    }
+
+    dirs.sort();
+    dirs
+}
+
+/// Frecency-ordered directories from zoxide, if it is installed
+async fn zoxide_directories() -> Vec<PathBuf> {
+    zoxide_scores()
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(_, dir)| dir)
+        .collect()
+}
+
+/// Directories zoxide knows with their frecency scores, highest first; `None` when zoxide is unavailable
+pub async fn zoxide_scores() -> Option<Vec<(f64, PathBuf)>> {
+    let output = Command::new("zoxide")
+        .args(["query", "-l", "--score"])
+        .output()
+        .await
+        .ok()?;
+
+    output
+        .status
+        .success()
+        .then(|| parse_zoxide_scores(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse `zoxide query -l --score` lines such as `  12.5 /home/me/src/app`
+fn parse_zoxide_scores(output: &str) -> Vec<(f64, PathBuf)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (score, dir) = line.trim_start().split_once(' ')?;
+            Some((score.parse().ok()?, PathBuf::from(dir.trim_start())))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_and_abbreviates_home() {
+        let home = dirs::home_dir().unwrap();
+
+        assert_eq!(expand_home("~/src/app"), home.join("src/app"));
+        assert_eq!(expand_home("~"), home);
+        assert_eq!(expand_home("/srv/app"), PathBuf::from("/srv/app"));
+        assert_eq!(expand_home("~other/app"), PathBuf::from("~other/app"));
+        assert_eq!(abbreviate_home(&home.join("src/app")), "~/src/app");
+        assert_eq!(abbreviate_home(Path::new("/srv/app")), "/srv/app");
+    }
+
+    #[tokio::test]
+    async fn lists_project_children_and_nested_repos() {
+        let root = tempfile::tempdir().unwrap();
+        for dir in ["api/.git", "org/web/.git", "org/notes", ".hidden"] {
+            std::fs::create_dir_all(root.path().join(dir)).unwrap();
+        }
+
+        let roots = vec![root.path().display().to_string()];
+        let candidates = candidate_directories(&roots).await;
+
+        assert!(candidates.contains(&root.path().join("api")));
+        assert!(candidates.contains(&root.path().join("org")));
+        assert!(candidates.contains(&root.path().join("org/web")));
+        assert!(!candidates.contains(&root.path().join("org/notes")));
+        assert!(!candidates.contains(&root.path().join(".hidden")));
+    }
+
+    #[test]
+    fn matches_sessions_by_recorded_path_first() {
+        let session = |name: &str, recorded: Option<&str>, current: Option<&str>| TmuxSession {
+            name: name.to_string(),
+            last_attached: chrono::Utc::now(),
+            windows: 1,
+            attached: false,
+            current_path: current.map(str::to_string),
+            options: crate::core::session::SessionOptions {
+                path: recorded.map(str::to_string),
+                ..Default::default()
+            },
+        };
+        let sessions = vec![
+            session("wandered", None, Some("/src/app")),
+            session("app", Some("/src/app"), Some("/tmp")),
+        ];
+
+        assert_eq!(find_session_for_dir(Path::new("/src/app"), &sessions).unwrap().name, "app");
+        assert_eq!(find_session_for_dir(Path::new("/tmp"), &sessions).unwrap().name, "app");
+        assert!(find_session_for_dir(Path::new("/src"), &sessions).is_none());
+    }
+
+    #[test]
+    fn parses_zoxide_scores() {
+        let output = "  48.0 /home/me/src/app\n   2.5 /home/me/My Documents\nnot a score\n";
+
+        assert_eq!(
+            parse_zoxide_scores(output),
+            vec![
+                (48.0, PathBuf::from("/home/me/src/app")),
+                (2.5, PathBuf::from("/home/me/My Documents")),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn derives_name_from_directory() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = root.path().join("my.app");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        assert_eq!(derive_session_name(&dir).await, "my_app");
+    }
+}
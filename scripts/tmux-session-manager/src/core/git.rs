@@ -0,0 +1,484 @@
+use anyhow::{anyhow, Result};
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+use crate::core::{directories, naming};
+
+/// One checkout listed by `git worktree list`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Worktree {
+    pub path: PathBuf,
+    /// Checked-out branch without `refs/heads/`; `None` when detached or bare
+    pub branch: Option<String>,
+    pub is_bare: bool,
+    /// Git reports its directory as gone; `git worktree prune` drops it
+    pub is_prunable: bool,
+}
+
+impl Worktree {
+    /// The worktree's directory name
+    pub fn name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.path.to_string_lossy().to_string())
+    }
+
+    /// Name of the repository this main worktree belongs to. A bare repository is named after
+    /// its directory without `.git` (`app.git`), or after the directory holding it when
+    /// hidden (`app/.bare`).
+    pub fn repo_name(&self) -> String {
+        if !self.is_bare {
+            return self.name();
+        }
+
+        let name = self.name();
+        match self.path.parent().and_then(Path::file_name) {
+            Some(parent) if name.starts_with('.') => parent.to_string_lossy().to_string(),
+            _ => name.strip_suffix(".git").map(str::to_string).unwrap_or(name),
+        }
+    }
+}
+
+/// Whether `dir` is the top of a git checkout (a `.git` directory, or a `.git` file for worktrees)
+pub fn is_repo_root(dir: &Path) -> bool {
+    dir.join(".git").exists()
+}
+
+/// The checkout or bare repository holding `dir`, found without running git
+pub fn checkout_of(dir: &Path) -> Option<&Path> {
+    dir.ancestors().find(|ancestor| {
+        is_repo_root(ancestor) || (ancestor.join("HEAD").is_file() && ancestor.join("objects").is_dir())
+    })
+}
+
+/// Whether `dir` is a linked worktree. Its `.git` is a file, but so is the one a bare layout
+/// (`repo/.bare` plus `repo/.git` pointing at it) keeps in the container directory; only a
+/// linked worktree has a git dir of its own, separate from the shared one.
+pub async fn is_worktree_dir(dir: &Path) -> bool {
+    if !dir.join(".git").is_file() {
+        return false;
+    }
+
+    match git_paths(dir, &["--git-dir", "--git-common-dir"]).await {
+        Ok(dirs) => matches!(dirs.as_slice(), [git_dir, common_dir] if git_dir != common_dir),
+        Err(_) => false,
+    }
+}
+
+/// Top level of the checkout containing `dir` (the worktree root for linked worktrees)
+pub async fn repo_root(dir: &Path) -> Option<PathBuf> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--show-toplevel"])
+        .current_dir(dir)
+        .output()
+        .await
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let root = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!root.is_empty()).then(|| PathBuf::from(root))
+}
+
+/// The repository directory shared by all worktrees of the one containing `dir`: `.git` of
+/// a regular checkout, or the bare repository itself (e.g. `repo/.bare`)
+pub async fn common_dir(dir: &Path) -> Option<PathBuf> {
+    let dirs = git_paths(dir, &["--git-common-dir"]).await.ok()?;
+    dirs.into_iter().next()
+}
+
+/// Every worktree of the repository containing `dir`, the main one first
+pub async fn list_worktrees(dir: &Path) -> Result<Vec<Worktree>> {
+    let output = Command::new("git")
+        .args(["worktree", "list", "--porcelain"])
+        .current_dir(dir)
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        return Err(anyhow!("Failed to list git worktrees in {}", dir.display()));
+    }
+
+    Ok(parse_worktree_list(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse `git worktree list --porcelain`: one block of `key value` lines per worktree
+pub fn parse_worktree_list(output: &str) -> Vec<Worktree> {
+    let mut worktrees = Vec::new();
+    let mut current: Option<Worktree> = None;
+
+    for line in output.lines() {
+        if let Some(path) = line.strip_prefix("worktree ") {
+            worktrees.extend(current.take());
+            current = Some(Worktree {
+                path: PathBuf::from(path),
+                branch: None,
+                is_bare: false,
+                is_prunable: false,
+            });
+        } else if let Some(worktree) = current.as_mut() {
+            if let Some(branch) = line.strip_prefix("branch ") {
+                worktree.branch = Some(branch.strip_prefix("refs/heads/").unwrap_or(branch).to_string());
+            } else if line == "bare" {
+                worktree.is_bare = true;
+            } else if line == "prunable" || line.starts_with("prunable ") {
+                worktree.is_prunable = true;
+            }
+        }
+    }
+    worktrees.extend(current);
+
+    worktrees
+}
+
+/// A branch that could be checked out into a new worktree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Branch {
+    /// Short name, e.g. `fix/login` or `origin/fix/login` for remote-tracking branches
+    pub name: String,
+    pub is_remote: bool,
+}
+
+impl Branch {
+    /// Name of the local branch checking this one out would use, e.g. `fix/login` for `origin/fix/login`
+    pub fn local_name(&self) -> &str {
+        match self.name.split_once('/') {
+            Some((_, name)) if self.is_remote => name,
+            _ => &self.name,
+        }
+    }
+}
+
+/// Local and remote-tracking branches of the repository containing `dir`
+pub async fn list_branches(dir: &Path) -> Result<Vec<Branch>> {
+    let output = Command::new("git")
+        .args(["for-each-ref", "--format=%(refname)", "refs/heads", "refs/remotes"])
+        .current_dir(dir)
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        return Err(anyhow!("Failed to list git branches in {}", dir.display()));
+    }
+
+    Ok(parse_branch_list(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse full ref names, skipping the `<remote>/HEAD` aliases
+fn parse_branch_list(output: &str) -> Vec<Branch> {
+    output
+        .lines()
+        .filter_map(|line| {
+            if let Some(name) = line.strip_prefix("refs/heads/") {
+                Some(Branch { name: name.to_string(), is_remote: false })
+            } else {
+                line.strip_prefix("refs/remotes/")
+                    .filter(|name| !name.ends_with("/HEAD"))
+                    .map(|name| Branch { name: name.to_string(), is_remote: true })
+            }
+        })
+        .collect()
+}
+
+/// Commit checked out in the worktree containing `dir`
+pub async fn head_commit(dir: &Path) -> Result<String> {
+    let lines = git_lines(dir, &["rev-parse", "HEAD"]).await?;
+    lines.into_iter().next().ok_or_else(|| anyhow!("No HEAD commit in {}", dir.display()))
+}
+
+/// Check out `branch` into a new worktree at `path`; with `base` the branch is created from it
+/// first (tracking it when `base` is a remote branch)
+pub async fn add_worktree(dir: &Path, path: &Path, branch: &str, base: Option<&str>) -> Result<()> {
+    let mut command = Command::new("git");
+    command.args(["worktree", "add"]).current_dir(dir);
+    match base {
+        Some(base) => command.args(["-b", branch]).arg(path).arg(base),
+        None => command.arg(path).arg(branch),
+    };
+
+    let output = command.output().await?;
+    if !output.status.success() {
+        return Err(anyhow!("git worktree add failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+
+    Ok(())
+}
+
+/// Where a new worktree goes, from a pattern using `{repo}` and `{branch}`. Relative patterns
+/// are placed beside the main checkout; slashes in branch names become dashes.
+pub fn worktree_location(pattern: &str, main_checkout: &Path, repo: &str, branch: &str) -> PathBuf {
+    let branch = branch.replace('/', "-");
+    let location = naming::fill_pattern(pattern, &[("repo", repo), ("branch", &branch)]);
+    let location = directories::expand_home(&location);
+
+    if location.is_absolute() {
+        location
+    } else {
+        main_checkout.parent().unwrap_or(main_checkout).join(location)
+    }
+}
+
+/// Work in a worktree that removing it could lose, or that only exists there
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorktreeStatus {
+    /// `git status --porcelain` lines
+    pub uncommitted: Vec<String>,
+    /// Commits not on the upstream branch (or on any remote when there is none)
+    pub unpushed: Vec<String>,
+    /// Stashes made on the worktree's branch
+    pub stashes: Vec<String>,
+}
+
+impl WorktreeStatus {
+    pub fn is_clean(&self) -> bool {
+        self.uncommitted.is_empty() && self.unpushed.is_empty() && self.stashes.is_empty()
+    }
+}
+
+/// Uncommitted changes, unpushed commits and stashes of the worktree at `dir`
+pub async fn worktree_status(dir: &Path) -> Result<WorktreeStatus> {
+    let uncommitted = git_lines(dir, &["status", "--porcelain"]).await?;
+
+    // Without an upstream, anything no remote has is unpushed; a repository without remotes has nothing to push to
+    let unpushed = if git_lines(dir, &["rev-parse", "--abbrev-ref", "@{upstream}"]).await.is_ok() {
+        git_lines(dir, &["log", "--oneline", "@{upstream}..HEAD"]).await?
+    } else if !git_lines(dir, &["remote"]).await?.is_empty() {
+        git_lines(dir, &["log", "--oneline", "HEAD", "--not", "--remotes"]).await?
+    } else {
+        Vec::new()
+    };
+
+    let stashes = match git_lines(dir, &["branch", "--show-current"]).await?.first() {
+        Some(branch) => stashes_on_branch(&git_lines(dir, &["stash", "list", "--format=%gd %gs"]).await?, branch),
+        None => Vec::new(),
+    };
+
+    Ok(WorktreeStatus { uncommitted, unpushed, stashes })
+}
+
+/// Stash entries (`stash@{0} WIP on main: ...` or `stash@{1} On main: ...`) recorded on `branch`
+fn stashes_on_branch(stashes: &[String], branch: &str) -> Vec<String> {
+    stashes
+        .iter()
+        .filter(|stash| is_stash_on_branch(stash, branch))
+        .cloned()
+        .collect()
+}
+
+/// Whether a stash entry, with or without its `stash@{n}` prefix, was made on `branch`
+pub(crate) fn is_stash_on_branch(stash: &str, branch: &str) -> bool {
+    // Ref names can't contain ':', so the first ": " ends the "WIP on <branch>" part
+    let head = stash.split(": ").next().unwrap_or(stash);
+    let head = head
+        .strip_prefix("stash@{")
+        .and_then(|rest| rest.split_once("} "))
+        .map_or(head, |(_, rest)| rest);
+
+    head == format!("WIP on {}", branch) || head == format!("On {}", branch)
+}
+
+/// Remove the worktree at `path`; `force` discards uncommitted changes in it
+pub async fn remove_worktree(repo: &Path, path: &Path, force: bool) -> Result<()> {
+    let mut args = vec!["worktree", "remove"];
+    if force {
+        args.push("--force");
+    }
+    let path = path.to_string_lossy();
+    args.push(&path);
+
+    git_lines(repo, &args).await.map(|_| ())
+}
+
+/// Drop the records of worktrees whose directories were deleted
+pub async fn prune_worktrees(repo: &Path) -> Result<()> {
+    git_lines(repo, &["worktree", "prune"]).await.map(|_| ())
+}
+
+/// Whether every commit of `branch` is already in `into`
+pub async fn is_merged(repo: &Path, branch: &str, into: &str) -> bool {
+    git_lines(repo, &["merge-base", "--is-ancestor", branch, into]).await.is_ok()
+}
+
+/// Delete a local branch, which git refuses unless it is merged
+pub async fn delete_branch(repo: &Path, branch: &str) -> Result<()> {
+    git_lines(repo, &["branch", "-d", branch]).await.map(|_| ())
+}
+
+/// Directories printed by `git rev-parse`, made absolute. Git prints them relative to `dir`
+/// unless asked otherwise, and `--path-format=absolute` needs git 2.31.
+async fn git_paths(dir: &Path, flags: &[&str]) -> Result<Vec<PathBuf>> {
+    let args: Vec<&str> = std::iter::once("rev-parse").chain(flags.iter().copied()).collect();
+    let paths = git_lines(dir, &args).await?;
+
+    Ok(paths
+        .into_iter()
+        .map(|path| {
+            let path = dir.join(path);
+            path.canonicalize().unwrap_or(path)
+        })
+        .collect())
+}
+
+/// Run git in `dir` and return its non-empty output lines, or its error output as the error
+async fn git_lines(dir: &Path, args: &[&str]) -> Result<Vec<String>> {
+    let output = Command::new("git").args(args).current_dir(dir).output().await?;
+
+    if !output.status.success() {
+        return Err(anyhow!("git {} failed: {}", args[0], String::from_utf8_lossy(&output.stderr).trim()));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// URL of `remote` for the repository containing `dir`
+pub async fn remote_url(dir: &Path, remote: &str) -> Option<String> {
+    let output = Command::new("git")
+        .args(["remote", "get-url", remote])
+        .current_dir(dir)
+        .output()
+        .await
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let url = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!url.is_empty()).then_some(url)
+}
+
+/// Repository name from a remote URL, e.g. `git@github.com:org/app.git` -> `app`
+pub fn repo_name_from_url(url: &str) -> Option<String> {
+    let url = url.trim().trim_end_matches('/');
+    let url = url.strip_suffix(".git").unwrap_or(url);
+
+    // scp-like URLs separate the host with ':' rather than '/'
+    url.rsplit(['/', ':'])
+        .next()
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_repo_name_from_remote_urls() {
+        assert_eq!(repo_name_from_url("git@github.com:org/app.git").as_deref(), Some("app"));
+        assert_eq!(repo_name_from_url("https://github.com/org/app").as_deref(), Some("app"));
+        assert_eq!(repo_name_from_url("https://github.com/org/app.git/").as_deref(), Some("app"));
+        assert_eq!(repo_name_from_url("git@host:app.git").as_deref(), Some("app"));
+        assert_eq!(repo_name_from_url("/srv/git/tools.git").as_deref(), Some("tools"));
+        assert_eq!(repo_name_from_url(""), None);
+    }
+
+    #[test]
+    fn parses_branch_list() {
+        let output = "refs/heads/main\nrefs/heads/fix/login\nrefs/remotes/origin/HEAD\nrefs/remotes/origin/fix/login\n";
+
+        let branches = parse_branch_list(output);
+
+        assert_eq!(branches.len(), 3);
+        assert_eq!(branches[1], Branch { name: "fix/login".to_string(), is_remote: false });
+        assert_eq!(branches[2].name, "origin/fix/login");
+        assert_eq!(branches[2].local_name(), "fix/login");
+    }
+
+    #[test]
+    fn names_bare_repositories_after_their_layout() {
+        let bare = |path: &str| Worktree { path: PathBuf::from(path), branch: None, is_bare: true, is_prunable: false };
+
+        assert_eq!(bare("/src/app/.bare").repo_name(), "app");
+        assert_eq!(bare("/src/app.git").repo_name(), "app");
+        assert_eq!(bare("/src/app").repo_name(), "app");
+        assert_eq!(Worktree { is_bare: false, ..bare("/src/web") }.repo_name(), "web");
+    }
+
+    #[test]
+    fn keeps_stashes_of_the_branch() {
+        let stashes = vec![
+            "stash@{0} WIP on fix/login: 1a2b wip".to_string(),
+            "stash@{1} On main: experiment".to_string(),
+            "stash@{2} On fix/login-v2: other".to_string(),
+        ];
+
+        assert_eq!(stashes_on_branch(&stashes, "fix/login"), vec!["stash@{0} WIP on fix/login: 1a2b wip"]);
+        assert_eq!(stashes_on_branch(&stashes, "main").len(), 1);
+        assert!(is_stash_on_branch("WIP on main: 1a2b wip", "main"));
+    }
+
+    #[test]
+    fn places_worktrees_from_location_pattern() {
+        let main = Path::new("/src/app");
+
+        assert_eq!(worktree_location("{repo}-{branch}", main, "app", "fix/login"), PathBuf::from("/src/app-fix-login"));
+        assert_eq!(worktree_location("/tmp/wt/{branch}", main, "app", "v2"), PathBuf::from("/tmp/wt/v2"));
+        assert_eq!(
+            worktree_location("~/worktrees/{repo}/{branch}", main, "app", "v2"),
+            dirs::home_dir().unwrap().join("worktrees/app/v2")
+        );
+    }
+
+    #[tokio::test]
+    async fn resolves_git_dirs_from_subdirectories() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().canonicalize().unwrap();
+        let (main, linked) = (root.join("app"), root.join("app-fix"));
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git").args(args).current_dir(&root).output().unwrap().status;
+            assert!(status.success(), "git {:?}", args);
+        };
+        git(&["init", "-q", "app"]);
+        git(&["-C", "app", "-c", "user.name=t", "-c", "user.email=t@t", "commit", "-q", "--allow-empty", "-m", "init"]);
+        git(&["-C", "app", "worktree", "add", "-q", "-b", "fix", "../app-fix"]);
+        std::fs::create_dir(main.join("src")).unwrap();
+
+        // git prints these relative to the directory it runs in
+        assert_eq!(common_dir(&main.join("src")).await, Some(main.join(".git")));
+        assert_eq!(common_dir(&linked).await, Some(main.join(".git")));
+        assert!(is_worktree_dir(&linked).await);
+        assert!(!is_worktree_dir(&main).await);
+    }
+
+    #[test]
+    fn finds_checkouts_without_running_git() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        std::fs::create_dir_all(root.join("app/.git")).unwrap();
+        std::fs::create_dir_all(root.join("app/src")).unwrap();
+        std::fs::create_dir_all(root.join("mirror.git/objects")).unwrap();
+        std::fs::write(root.join("mirror.git/HEAD"), "ref: refs/heads/main\n").unwrap();
+        std::fs::create_dir_all(root.join("notes")).unwrap();
+
+        assert_eq!(checkout_of(&root.join("app/src")), Some(root.join("app").as_path()));
+        assert_eq!(checkout_of(&root.join("mirror.git")), Some(root.join("mirror.git").as_path()));
+        assert_eq!(checkout_of(&root.join("notes")), None);
+    }
+
+    #[test]
+    fn parses_worktree_list() {
+        let output = "worktree /src/app\nHEAD 1a2b\nbranch refs/heads/main\n\n\
+                      worktree /src/app-fix\nHEAD 3c4d\nbranch refs/heads/fix/login\n\n\
+                      worktree /src/app-review\nHEAD 5e6f\ndetached\n\n\
+                      worktree /src/app-old\nHEAD 7a8b\nbranch refs/heads/old\nprunable gitdir file points to non-existent location\n";
+
+        let worktrees = parse_worktree_list(output);
+
+        assert_eq!(worktrees.len(), 4);
+        assert_eq!(worktrees[0].branch.as_deref(), Some("main"));
+        assert_eq!(worktrees[1].branch.as_deref(), Some("fix/login"));
+        assert_eq!(worktrees[1].name(), "app-fix");
+        assert_eq!(worktrees[2].path, PathBuf::from("/src/app-review"));
+        assert_eq!(worktrees[2].branch, None);
+        assert!(!worktrees[2].is_prunable);
+        assert!(worktrees[3].is_prunable);
+    }
+}
@@ -0,0 +1,371 @@
+use anyhow::Result;
+use chrono::{DateTime, TimeZone, Utc};
+use git2::{BranchType, Repository, Status, StatusOptions};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+
+use crate::core::git;
+use crate::core::session::format_age;
+
+/// Changed files kept for the preview; the counts cover every file
+const MAX_CHANGED_FILES: usize = 10;
+
+/// Branches a worktree's branch is checked for being merged into, in order of preference
+const MAIN_BRANCHES: &[&str] = &["main", "master"];
+
+/// State of a checkout, read in-process with libgit2
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GitStatus {
+    /// Checked-out branch; `None` when detached
+    pub branch: Option<String>,
+    /// Commits ahead of and behind the upstream; `None` without one
+    pub ahead_behind: Option<(usize, usize)>,
+    pub staged: usize,
+    pub modified: usize,
+    pub untracked: usize,
+    pub conflicted: usize,
+    /// Stashes made on the branch
+    pub stashes: usize,
+    pub last_commit: Option<DateTime<Utc>>,
+    pub last_commit_summary: Option<String>,
+    /// The main branch and whether the branch is fully merged into it; `None` on the main branch itself
+    pub merged_into: Option<(String, bool)>,
+    /// The first few changed files as `XY path`, like `git status --short`
+    pub changed_files: Vec<String>,
+}
+
+impl GitStatus {
+    /// Read the status of the checkout containing `path`
+    pub fn read(path: &Path) -> Result<Self> {
+        let mut repo = Repository::discover(path)?;
+        let mut status = GitStatus::default();
+
+        let head = repo.head().ok();
+        let head_oid = head.as_ref().and_then(|head| head.target());
+        status.branch = head
+            .as_ref()
+            .filter(|head| head.is_branch())
+            .and_then(|head| head.shorthand().ok())
+            .map(str::to_string);
+
+        if let (Some(branch), Some(head_oid)) = (&status.branch, head_oid) {
+            let upstream_oid = repo
+                .find_branch(branch, BranchType::Local)
+                .and_then(|local| local.upstream())
+                .ok()
+                .and_then(|upstream| upstream.get().target());
+            if let Some(upstream_oid) = upstream_oid {
+                status.ahead_behind = repo.graph_ahead_behind(head_oid, upstream_oid).ok();
+            }
+
+            let main_branch = MAIN_BRANCHES
+                .iter()
+                .find_map(|name| repo.find_branch(name, BranchType::Local).ok().map(|found| (*name, found)));
+            if let Some((main_name, main_branch)) = main_branch.filter(|(name, _)| name != branch) {
+                if let Some(main_oid) = main_branch.get().target() {
+                    let merged = main_oid == head_oid || repo.graph_descendant_of(main_oid, head_oid).unwrap_or(false);
+                    status.merged_into = Some((main_name.to_string(), merged));
+                }
+            }
+        }
+
+        if let Some(commit) = head.as_ref().and_then(|head| head.peel_to_commit().ok()) {
+            status.last_commit = Utc.timestamp_opt(commit.time().seconds(), 0).single();
+            status.last_commit_summary = commit.summary().ok().flatten().map(str::to_string);
+        }
+        drop(head);
+
+        status.count_changes(&repo)?;
+
+        if let Some(branch) = status.branch.clone() {
+            let mut stashes = 0;
+            repo.stash_foreach(|_, message, _| {
+                if git::is_stash_on_branch(message, &branch) {
+                    stashes += 1;
+                }
+                true
+            })?;
+            status.stashes = stashes;
+        }
+
+        Ok(status)
+    }
+
+    fn count_changes(&mut self, repo: &Repository) -> Result<()> {
+        let mut options = StatusOptions::new();
+        options
+            .include_untracked(true)
+            .recurse_untracked_dirs(false)
+            .exclude_submodules(true);
+
+        let staged_flags = Status::INDEX_NEW | Status::INDEX_MODIFIED | Status::INDEX_DELETED | Status::INDEX_RENAMED | Status::INDEX_TYPECHANGE;
+        let modified_flags = Status::WT_MODIFIED | Status::WT_DELETED | Status::WT_RENAMED | Status::WT_TYPECHANGE;
+
+        for entry in repo.statuses(Some(&mut options))?.iter() {
+            let flags = entry.status();
+            if flags.is_ignored() {
+                continue;
+            }
+
+            let code = if flags.is_conflicted() {
+                self.conflicted += 1;
+                "UU"
+            } else if flags.is_wt_new() {
+                self.untracked += 1;
+                "??"
+            } else {
+                let staged = flags.intersects(staged_flags);
+                let modified = flags.intersects(modified_flags);
+                self.staged += usize::from(staged);
+                self.modified += usize::from(modified);
+                match (staged, modified) {
+                    (true, true) => "MM",
+                    (true, false) => "M ",
+                    _ => " M",
+                }
+            };
+
+            if self.changed_files.len() < MAX_CHANGED_FILES {
+                self.changed_files.push(format!("{} {}", code, entry.path().unwrap_or("?")));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.staged + self.modified + self.untracked + self.conflicted > 0
+    }
+
+    /// Compact, colored summary for list lines, e.g. `↑2 ↓1 +1 ~3 ?2 ≡1 3d`
+    pub fn badges(&self) -> String {
+        let mut badges = Vec::new();
+
+        if let Some((ahead, behind)) = self.ahead_behind {
+            if ahead > 0 {
+                badges.push(format!("\x1b[0;32m↑{}\x1b[0m", ahead));
+            }
+            if behind > 0 {
+                badges.push(format!("\x1b[0;31m↓{}\x1b[0m", behind));
+            }
+        }
+        for (count, symbol, color) in [
+            (self.conflicted, "!", "\x1b[1;31m"),
+            (self.staged, "+", "\x1b[0;32m"),
+            (self.modified, "~", "\x1b[0;33m"),
+            (self.untracked, "?", "\x1b[0;90m"),
+            (self.stashes, "≡", "\x1b[0;36m"),
+        ] {
+            if count > 0 {
+                badges.push(format!("{}{}{}\x1b[0m", color, symbol, count));
+            }
+        }
+        if let Some((_, true)) = self.merged_into {
+            badges.push("\x1b[0;90mmerged\x1b[0m".to_string());
+        }
+        if let Some(last_commit) = self.last_commit {
+            // Only the leading unit, so the badge stays short
+            let age = format_age(Utc::now() - last_commit);
+            badges.push(format!("\x1b[0;90m{}\x1b[0m", age.split_whitespace().next().unwrap_or(&age)));
+        }
+
+        badges.join(" ")
+    }
+
+    /// Labelled lines for the preview
+    pub fn describe(&self) -> String {
+        let mut lines = Vec::new();
+        let label = |name: &str, value: String| format!("\x1b[1;33m{}:\x1b[0m {}", name, value);
+
+        lines.push(label("Branch", self.branch.clone().unwrap_or_else(|| "(detached)".to_string())));
+        lines.push(label("Upstream", match self.ahead_behind {
+            Some((0, 0)) => "up to date".to_string(),
+            Some((ahead, behind)) => format!("{} ahead, {} behind", ahead, behind),
+            None => "none".to_string(),
+        }));
+        if let Some((main, merged)) = &self.merged_into {
+            lines.push(label("Merged", format!("{} into {}", if *merged { "yes" } else { "not yet" }, main)));
+        }
+        if let Some(last_commit) = self.last_commit {
+            let summary = self.last_commit_summary.as_deref().unwrap_or("");
+            lines.push(label("Last commit", format!("{} ago - {}", format_age(Utc::now() - last_commit), summary)));
+        }
+        lines.push(label("Changes", format!(
+            "{} staged, {} modified, {} untracked, {} conflicted",
+            self.staged, self.modified, self.untracked, self.conflicted
+        )));
+        if self.stashes > 0 {
+            lines.push(label("Stashes", self.stashes.to_string()));
+        }
+        for file in &self.changed_files {
+            lines.push(format!("  {}", file));
+        }
+
+        lines.join("\n") + "\n"
+    }
+}
+
+/// The latest `count` commits of the checkout containing `path`, as `<short id> <summary>`
+pub fn recent_commits(path: &Path, count: usize) -> Result<Vec<String>> {
+    let repo = Repository::discover(path)?;
+    let mut walk = repo.revwalk()?;
+    walk.push_head()?;
+
+    let mut commits = Vec::new();
+    for oid in walk.take(count) {
+        let commit = repo.find_commit(oid?)?;
+        let id = commit.id().to_string();
+        commits.push(format!("{} {}", &id[..7], commit.summary().ok().flatten().unwrap_or("")));
+    }
+    Ok(commits)
+}
+
+/// Statuses kept in memory for one process. Entries are reused for a short while, so the
+/// interactive loop redrawing the list after an action doesn't read every checkout again.
+pub struct StatusCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<PathBuf, (Instant, GitStatus)>>,
+}
+
+impl Default for StatusCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StatusCache {
+    pub fn new() -> Self {
+        Self {
+            ttl: Duration::from_secs(10),
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Statuses of `paths`, reading the ones not cached concurrently; unreadable paths are left out
+    pub async fn statuses(&self, paths: &[PathBuf]) -> HashMap<PathBuf, GitStatus> {
+        let mut statuses = HashMap::new();
+        let mut reads = JoinSet::new();
+
+        {
+            let entries = self.entries.lock().unwrap();
+            for path in paths {
+                match entries.get(path) {
+                    Some((read_at, status)) if read_at.elapsed() < self.ttl => {
+                        statuses.insert(path.clone(), status.clone());
+                    }
+                    _ => {
+                        let path = path.clone();
+                        reads.spawn_blocking(move || (GitStatus::read(&path), path));
+                    }
+                }
+            }
+        }
+
+        while let Some(read) = reads.join_next().await {
+            if let Ok((Ok(status), path)) = read {
+                self.entries.lock().unwrap().insert(path.clone(), (Instant::now(), status.clone()));
+                statuses.insert(path, status);
+            }
+        }
+
+        statuses
+    }
+
+    pub async fn status(&self, path: &Path) -> Option<GitStatus> {
+        self.statuses(&[path.to_path_buf()]).await.remove(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A repository on `main` with one commit of `tracked.txt`
+    fn repo_with_commit(dir: &Path) -> Repository {
+        let repo = Repository::init(dir).unwrap();
+        repo.set_head("refs/heads/main").unwrap();
+        std::fs::write(dir.join("tracked.txt"), "one").unwrap();
+        commit_all(&repo, "init");
+        repo
+    }
+
+    fn commit_all(repo: &Repository, message: &str) {
+        let mut index = repo.index().unwrap();
+        index.add_all(["*"], git2::IndexAddOption::DEFAULT, None).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("test", "test@example.com").unwrap();
+        let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &signature, &signature, message, &tree, &parents).unwrap();
+    }
+
+    #[test]
+    fn counts_changes_and_merge_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = repo_with_commit(dir.path());
+
+        // A branch one commit ahead of main, then a staged, a modified and an untracked file
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.branch("feature", &head, false).unwrap();
+        repo.set_head("refs/heads/feature").unwrap();
+        std::fs::write(dir.path().join("feature.txt"), "new").unwrap();
+        commit_all(&repo, "add feature");
+
+        std::fs::write(dir.path().join("staged.txt"), "staged").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("staged.txt")).unwrap();
+        index.write().unwrap();
+        std::fs::write(dir.path().join("tracked.txt"), "two").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "draft").unwrap();
+
+        let status = GitStatus::read(dir.path()).unwrap();
+
+        assert_eq!(status.branch.as_deref(), Some("feature"));
+        assert_eq!((status.staged, status.modified, status.untracked), (1, 1, 1));
+        assert_eq!(status.merged_into, Some(("main".to_string(), false)));
+        assert_eq!(status.ahead_behind, None);
+        assert_eq!(status.last_commit_summary.as_deref(), Some("add feature"));
+        assert!(status.is_dirty());
+        assert!(status.changed_files.contains(&"?? notes.txt".to_string()));
+        assert_eq!(recent_commits(dir.path(), 5).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn badges_show_only_what_is_set() {
+        let status = GitStatus {
+            ahead_behind: Some((2, 0)),
+            modified: 3,
+            merged_into: Some(("main".to_string(), true)),
+            ..Default::default()
+        };
+
+        assert_eq!(status.badges(), "\x1b[0;32m↑2\x1b[0m \x1b[0;33m~3\x1b[0m \x1b[0;90mmerged\x1b[0m");
+        assert_eq!(GitStatus::default().badges(), "");
+    }
+
+    #[tokio::test]
+    async fn caches_statuses_and_skips_unreadable_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        repo_with_commit(dir.path());
+        let missing = dir.path().join("missing");
+        let cache = StatusCache::new();
+
+        let statuses = cache.statuses(&[dir.path().to_path_buf(), missing]).await;
+        assert_eq!(statuses.len(), 1);
+
+        // Within the TTL a new file is not noticed yet
+        std::fs::write(dir.path().join("later.txt"), "x").unwrap();
+        assert_eq!(cache.status(dir.path()).await.unwrap().untracked, 0);
+        let fresh = StatusCache::new().with_ttl(Duration::ZERO);
+        assert_eq!(fresh.status(dir.path()).await.unwrap().untracked, 1);
+    }
+}
@@ -0,0 +1,137 @@
+use anyhow::{anyhow, Result};
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+/// One workspace listed by `jj workspace list`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Workspace {
+    pub name: String,
+    /// Where the workspace is checked out; `None` when it could not be located
+    pub path: Option<PathBuf>,
+    /// The working-copy commit line jj prints after the name
+    pub summary: String,
+}
+
+/// Whether `dir` is a workspace besides the repository's default one; those keep a
+/// `.jj/repo` file pointing at the shared repository instead of the repository itself
+pub fn is_secondary_workspace(dir: &Path) -> bool {
+    dir.join(".jj").join("repo").is_file()
+}
+
+/// Root of the repository's default workspace, for any directory inside one of its workspaces
+pub async fn repo_root(dir: &Path) -> Option<PathBuf> {
+    let workspace = PathBuf::from(jj_lines(dir, &["root", "--ignore-working-copy"]).await.ok()?.first()?);
+    let pointer = workspace.join(".jj").join("repo");
+    if !pointer.is_file() {
+        return Some(workspace);
+    }
+
+    // The pointer holds `<default workspace>/.jj/repo`, relative to this `.jj` when not absolute
+    let target = std::fs::read_to_string(&pointer).ok()?;
+    let repo = workspace.join(".jj").join(target.trim());
+    let repo = repo.canonicalize().unwrap_or(repo);
+    repo.parent()?.parent().map(Path::to_path_buf)
+}
+
+/// Every workspace of the repository whose default workspace is `root`, the default one first
+pub async fn list_workspaces(root: &Path) -> Result<Vec<Workspace>> {
+    let output = jj_lines(root, &["workspace", "list", "--ignore-working-copy"]).await?;
+    let mut workspaces = parse_workspace_list(&output.join("\n"));
+    workspaces.sort_by_key(|workspace| workspace.name != "default");
+
+    for workspace in &mut workspaces {
+        workspace.path = workspace_path(root, &workspace.name).await;
+    }
+    Ok(workspaces)
+}
+
+/// Parse `jj workspace list` lines such as `feature: zsuskuln 77a4ee2b add login form`
+fn parse_workspace_list(output: &str) -> Vec<Workspace> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (name, summary) = line.split_once(": ")?;
+            Some(Workspace {
+                name: name.trim().to_string(),
+                path: None,
+                summary: summary.trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Ask jj where a workspace lives; jj versions without `workspace root --name` only
+/// allow guessing a default of `jj workspace add ../<name>`, which is a sibling directory
+async fn workspace_path(root: &Path, name: &str) -> Option<PathBuf> {
+    if let Some(path) = jj_lines(root, &["workspace", "root", "--ignore-working-copy", "--name", name])
+        .await
+        .ok()
+        .and_then(|lines| lines.first().map(PathBuf::from))
+    {
+        return Some(path);
+    }
+
+    if name == "default" {
+        return Some(root.to_path_buf());
+    }
+    let sibling = root.parent()?.join(name);
+    is_secondary_workspace(&sibling).then_some(sibling)
+}
+
+/// Description of the workspace's working-copy change, if it has one
+pub async fn working_copy_description(dir: &Path) -> Option<String> {
+    let lines = jj_lines(dir, &["log", "-r", "@", "--no-graph", "--color", "never", "-T", "description"])
+        .await
+        .ok()?;
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+/// `jj log` as seen from the workspace, colored for the preview
+pub async fn log(dir: &Path, limit: usize) -> Result<String> {
+    let limit = limit.to_string();
+    Ok(jj_lines(dir, &["log", "--color", "always", "--limit", &limit]).await?.join("\n"))
+}
+
+/// Run jj in `dir` and return its non-empty output lines, or its error output as the error
+async fn jj_lines(dir: &Path, args: &[&str]) -> Result<Vec<String>> {
+    let output = Command::new("jj").args(args).current_dir(dir).output().await?;
+
+    if !output.status.success() {
+        return Err(anyhow!("jj {} failed: {}", args[0], String::from_utf8_lossy(&output.stderr).trim()));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_workspace_list() {
+        let output = "default: rlvkpnrz 2c0a8d7e (empty) (no description set)\n\
+                      feature: zsuskuln 77a4ee2b add login form\n";
+
+        let workspaces = parse_workspace_list(output);
+
+        assert_eq!(workspaces.len(), 2);
+        assert_eq!(workspaces[0].name, "default");
+        assert_eq!(workspaces[1].name, "feature");
+        assert_eq!(workspaces[1].summary, "zsuskuln 77a4ee2b add login form");
+    }
+
+    #[test]
+    fn tells_secondary_workspaces_by_their_repo_pointer() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(root.path().join("app/.jj/repo")).unwrap();
+        std::fs::create_dir_all(root.path().join("feature/.jj")).unwrap();
+        std::fs::write(root.path().join("feature/.jj/repo"), "../../app/.jj/repo").unwrap();
+
+        assert!(!is_secondary_workspace(&root.path().join("app")));
+        assert!(is_secondary_workspace(&root.path().join("feature")));
+    }
+}
@@ -1,3 +1,12 @@
+pub mod directories;
+pub mod git;
+pub mod git_status;
+pub mod jj;
+pub mod naming;
+pub mod prune;
+pub mod repo_scan;
 pub mod session;
+pub mod template;
 pub mod tmux;
-pub mod ui;
\ No newline at end of file
+pub mod trash;
+pub mod ui;
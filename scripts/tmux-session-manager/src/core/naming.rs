@@ -0,0 +1,158 @@
+use crate::core::session::TmuxSession;
+
+/// Used when a name sanitizes down to nothing (e.g. "...")
+const FALLBACK_NAME: &str = "session";
+
+/// Map any project, directory or branch name to a valid tmux session name.
+///
+/// tmux treats ':' and '.' as target separators and dislikes a leading dot, so
+/// those (along with whitespace and control characters) become underscores.
+pub fn sanitize(name: &str) -> String {
+    let sanitized: String = name
+        .trim()
+        .trim_start_matches('.')
+        .chars()
+        .map(|c| match c {
+            ':' | '.' => '_',
+            c if c.is_whitespace() || c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+
+    if sanitized.is_empty() {
+        FALLBACK_NAME.to_string()
+    } else {
+        sanitized
+    }
+}
+
+/// Fill `{key}` placeholders in a naming pattern such as `{repo}/{branch}`;
+/// unknown placeholders are left as written
+pub fn fill_pattern(pattern: &str, values: &[(&str, &str)]) -> String {
+    values.iter().fold(pattern.to_string(), |name, (key, value)| {
+        name.replace(&format!("{{{}}}", key), value)
+    })
+}
+
+/// Picker name and session identity of a checkout (a git worktree or jj workspace) from the
+/// `worktrees.naming` pattern; the tmux session name is this passed through `sanitize`
+pub fn checkout_identity(pattern: &str, repo: &str, branch: &str, worktree: &str) -> String {
+    fill_pattern(pattern, &[("repo", repo), ("branch", branch), ("worktree", worktree)])
+}
+
+/// `base`, or `base-2`, `base-3`, ... whichever is not yet taken
+pub fn unique_name(base: &str, is_taken: impl Fn(&str) -> bool) -> String {
+    if !is_taken(base) {
+        return base.to_string();
+    }
+
+    (2..)
+        .map(|suffix| format!("{}-{}", base, suffix))
+        .find(|candidate| !is_taken(candidate))
+        .expect("an unused suffix always exists")
+}
+
+/// The running session created for `identity`.
+///
+/// Sessions record their identity in `@tsm_identity`; older sessions without
+/// it match when their name equals the identity or its sanitized form.
+pub fn find_session<'a>(identity: &str, sessions: &'a [TmuxSession]) -> Option<&'a TmuxSession> {
+    sessions
+        .iter()
+        .find(|session| session.options.identity.as_deref() == Some(identity))
+        .or_else(|| {
+            let sanitized = sanitize(identity);
+            sessions.iter().find(|session| {
+                session.options.identity.is_none() && (session.name == identity || session.name == sanitized)
+            })
+        })
+}
+
+/// The identity a session was created for, falling back to its name
+pub fn identity_of(session: &TmuxSession) -> &str {
+    session.options.identity.as_deref().unwrap_or(&session.name)
+}
+
+/// The tmux name to use for `identity`: its existing session's name, or a
+/// sanitized name no other running session uses
+pub fn session_name_for(identity: &str, sessions: &[TmuxSession]) -> String {
+    if let Some(session) = find_session(identity, sessions) {
+        return session.name.clone();
+    }
+
+    unique_name(&sanitize(identity), |candidate| {
+        sessions.iter().any(|session| session.name == candidate)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::session::{validate_session_name, SessionOptions};
+    use chrono::Utc;
+
+    fn session(name: &str, identity: Option<&str>) -> TmuxSession {
+        TmuxSession {
+            name: name.to_string(),
+            last_attached: Utc::now(),
+            windows: 1,
+            attached: false,
+            current_path: None,
+            options: SessionOptions {
+                identity: identity.map(str::to_string),
+                ..SessionOptions::default()
+            },
+        }
+    }
+
+    #[test]
+    fn sanitizes_tmux_target_separators() {
+        assert_eq!(sanitize(".config"), "config");
+        assert_eq!(sanitize("release/v1.2"), "release/v1_2");
+        assert_eq!(sanitize("host:8080"), "host_8080");
+        assert_eq!(sanitize(" my project "), "my_project");
+        assert_eq!(sanitize("..."), "session");
+
+        for name in [".config", "release/v1.2", "a:b.c", "tab\there"] {
+            assert!(validate_session_name(&sanitize(name)).is_ok(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn fills_naming_patterns() {
+        let values = [("repo", "app"), ("branch", "fix/login")];
+
+        assert_eq!(fill_pattern("{repo}/{branch}", &values), "app/fix/login");
+        assert_eq!(fill_pattern("{repo}-{repo}", &values), "app-app");
+        assert_eq!(fill_pattern("{repo}-{other}", &values), "app-{other}");
+    }
+
+    #[test]
+    fn appends_suffix_until_unique() {
+        let taken = ["api", "api-2"];
+        assert_eq!(unique_name("web", |name| taken.contains(&name)), "web");
+        assert_eq!(unique_name("api", |name| taken.contains(&name)), "api-3");
+    }
+
+    #[test]
+    fn maps_identities_back_to_sessions() {
+        let sessions = vec![
+            session("v1_2", Some("v1.2")),
+            session("v1_2-2", Some("v1:2")),
+            session("legacy", None),
+        ];
+
+        assert_eq!(find_session("v1:2", &sessions).unwrap().name, "v1_2-2");
+        assert_eq!(find_session("legacy", &sessions).unwrap().name, "legacy");
+        assert!(find_session("v1_2", &sessions).is_none());
+        assert_eq!(identity_of(&sessions[0]), "v1.2");
+    }
+
+    #[test]
+    fn avoids_collisions_between_distinct_identities() {
+        let sessions = vec![session("v1_2", Some("v1.2"))];
+
+        assert_eq!(session_name_for("v1.2", &sessions), "v1_2");
+        assert_eq!(session_name_for("v1:2", &sessions), "v1_2-2");
+    }
+}
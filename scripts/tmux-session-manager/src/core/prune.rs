@@ -0,0 +1,264 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::config::{matches_any_pattern, Config};
+use crate::core::session::{format_age, SessionContext, TmuxPane, TmuxSession};
+use crate::core::tmux::TmuxClient;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PruneReason {
+    /// The session's working directory no longer exists (e.g. a removed worktree)
+    MissingDirectory(String),
+    /// Not attached for longer than the idle threshold
+    Idle(Duration),
+    /// Detached with a single pane sitting at a shell prompt
+    IdleShell,
+}
+
+impl std::fmt::Display for PruneReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PruneReason::MissingDirectory(path) => write!(f, "directory deleted: {}", path),
+            PruneReason::Idle(age) => write!(f, "idle for {}", format_age(*age)),
+            PruneReason::IdleShell => write!(f, "detached idle shell"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PruneCandidate {
+    pub session_name: String,
+    pub last_attached: DateTime<Utc>,
+    pub reason: PruneReason,
+}
+
+/// Decides which sessions are stale enough to be offered for cleanup
+#[derive(Debug, Clone)]
+pub struct PruneRules {
+    pub idle_threshold: Duration,
+    pub pinned_sessions: Vec<String>,
+    pub session_tags: HashMap<String, Vec<String>>,
+    pub whitelist_tags: Vec<String>,
+    pub protected_patterns: Vec<String>,
+}
+
+impl PruneRules {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            idle_threshold: Duration::hours(config.prune.idle_threshold_hours),
+            pinned_sessions: config.pinned_sessions.clone(),
+            session_tags: config.session_tags.clone(),
+            whitelist_tags: config.prune.whitelist_tags.clone(),
+            protected_patterns: config.protected_sessions.clone(),
+        }
+    }
+
+    pub fn with_idle_threshold(mut self, idle_threshold: Duration) -> Self {
+        self.idle_threshold = idle_threshold;
+        self
+    }
+
+    pub fn is_whitelisted(&self, session_name: &str) -> bool {
+        if self.pinned_sessions.iter().any(|name| name == session_name) {
+            return true;
+        }
+
+        self.session_tags
+            .get(session_name)
+            .map(|tags| tags.iter().any(|tag| self.whitelist_tags.contains(tag)))
+            .unwrap_or(false)
+    }
+
+    /// Classify a single session; `None` means it should be kept
+    pub fn classify(&self, session: &TmuxSession, panes: &[TmuxPane], now: DateTime<Utc>) -> Option<PruneReason> {
+        if session.attached
+            || session.options.protected
+            || matches_any_pattern(&self.protected_patterns, &session.name)
+            || self.is_whitelisted(&session.name)
+        {
+            return None;
+        }
+
+        // Linux reports the cwd of a shell whose directory was removed as "<path> (deleted)";
+        // tmux leaves the path empty when it cannot read it at all, which proves nothing
+        if let Some(pane) = panes.iter().filter(|pane| !pane.current_path.is_empty()).find(|pane| {
+            pane.current_path.ends_with(" (deleted)") || !Path::new(&pane.current_path).exists()
+        }) {
+            let path = pane.current_path.trim_end_matches(" (deleted)");
+            return Some(PruneReason::MissingDirectory(path.to_string()));
+        }
+
+        // Never-attached sessions have no meaningful idle time (see parse_session_line)
+        let idle = now - session.last_attached;
+        if session.last_attached.timestamp() > 0 && idle > self.idle_threshold {
+            return Some(PruneReason::Idle(idle));
+        }
+
+        if let [pane] = panes {
+            if !pane.is_busy() {
+                return Some(PruneReason::IdleShell);
+            }
+        }
+
+        None
+    }
+
+    pub async fn find_candidates(&self, tmux: &TmuxClient, context: &SessionContext) -> Result<Vec<PruneCandidate>> {
+        let now = Utc::now();
+        let mut candidates = Vec::new();
+
+        for session in &context.all_tmux_sessions {
+            // Outside tmux the "current" session is just tmux's best guess, not one in use
+            if TmuxClient::is_inside_tmux() && context.current_session.as_ref() == Some(&session.name) {
+                continue;
+            }
+
+            let panes = tmux.list_panes(&session.name).await.unwrap_or_default();
+            if let Some(reason) = self.classify(session, &panes, now) {
+                candidates.push(PruneCandidate {
+                    session_name: session.name.clone(),
+                    last_attached: session.last_attached,
+                    reason,
+                });
+            }
+        }
+
+        Ok(candidates)
+    }
+}
+
+/// Render candidates as aligned rows; the session name is always the first column
+pub fn format_table(candidates: &[PruneCandidate]) -> (String, Vec<String>) {
+    let width = candidates
+        .iter()
+        .map(|candidate| candidate.session_name.len())
+        .chain(std::iter::once("SESSION".len()))
+        .max()
+        .unwrap_or(0);
+
+    let header = format!("{:<width$}  {:<14}  REASON", "SESSION", "LAST ATTACHED", width = width);
+    let now = Utc::now();
+    let rows = candidates
+        .iter()
+        .map(|candidate| {
+            let last_attached = if candidate.last_attached.timestamp() == 0 {
+                "never".to_string()
+            } else {
+                format!("{} ago", format_age(now - candidate.last_attached))
+            };
+            format!("{:<width$}  {:<14}  {}", candidate.session_name, last_attached, candidate.reason, width = width)
+        })
+        .collect();
+
+    (header, rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::session::SessionOptions;
+    use chrono::TimeZone;
+
+    fn rules() -> PruneRules {
+        PruneRules::from_config(&Config::default())
+    }
+
+    fn session(name: &str, last_attached: DateTime<Utc>, attached: bool) -> TmuxSession {
+        TmuxSession {
+            name: name.to_string(),
+            last_attached,
+            windows: 1,
+            attached,
+            current_path: None,
+            options: SessionOptions::default(),
+        }
+    }
+
+    fn pane(command: &str, path: &str) -> TmuxPane {
+        TmuxPane {
+            window_index: 1,
+            window_name: command.to_string(),
+            window_layout: String::new(),
+            window_active: true,
+            pane_index: 0,
+            pane_active: true,
+            current_command: command.to_string(),
+            current_path: path.to_string(),
+        }
+    }
+
+    #[test]
+    fn flags_sessions_idle_beyond_threshold() {
+        let now = Utc.timestamp_opt(1_700_000_000, 0).single().unwrap();
+        let old = session("old", now - Duration::days(5), false);
+
+        let reason = rules().classify(&old, &[pane("nvim", "/"), pane("zsh", "/")], now);
+        assert_eq!(reason, Some(PruneReason::Idle(Duration::days(5))));
+    }
+
+    #[test]
+    fn flags_detached_idle_shell() {
+        let now = Utc.timestamp_opt(1_700_000_000, 0).single().unwrap();
+        let recent = session("shell", now - Duration::minutes(5), false);
+
+        assert_eq!(rules().classify(&recent, &[pane("zsh", "/")], now), Some(PruneReason::IdleShell));
+        assert_eq!(rules().classify(&recent, &[pane("nvim", "/")], now), None);
+    }
+
+    #[test]
+    fn flags_deleted_directories() {
+        let now = Utc.timestamp_opt(1_700_000_000, 0).single().unwrap();
+        let recent = session("gone", now, false);
+
+        let reason = rules().classify(&recent, &[pane("nvim", "/definitely/not/here")], now);
+        assert_eq!(reason, Some(PruneReason::MissingDirectory("/definitely/not/here".to_string())));
+
+        let reason = rules().classify(&recent, &[pane("zsh", "/tmp (deleted)")], now);
+        assert_eq!(reason, Some(PruneReason::MissingDirectory("/tmp".to_string())));
+    }
+
+    #[test]
+    fn unreadable_paths_are_not_missing_directories() {
+        let now = Utc.timestamp_opt(1_700_000_000, 0).single().unwrap();
+        let recent = session("unknown", now, false);
+
+        assert_eq!(rules().classify(&recent, &[pane("nvim", "")], now), None);
+    }
+
+    #[test]
+    fn never_attached_sessions_are_not_idle() {
+        let now = Utc.timestamp_opt(1_700_000_000, 0).single().unwrap();
+        let never = session("never", Utc.timestamp_opt(0, 0).single().unwrap(), false);
+
+        assert_eq!(rules().classify(&never, &[pane("nvim", "/")], now), None);
+    }
+
+    #[test]
+    fn keeps_attached_pinned_and_whitelisted_sessions() {
+        let now = Utc.timestamp_opt(1_700_000_000, 0).single().unwrap();
+        let mut rules = rules();
+        rules.pinned_sessions.push("pinned".to_string());
+        rules.session_tags.insert("infra".to_string(), vec!["keep".to_string()]);
+
+        let panes = [pane("zsh", "/")];
+        assert_eq!(rules.classify(&session("attached", now - Duration::days(9), true), &panes, now), None);
+        assert_eq!(rules.classify(&session("pinned", now - Duration::days(9), false), &panes, now), None);
+        assert_eq!(rules.classify(&session("infra", now - Duration::days(9), false), &panes, now), None);
+    }
+
+    #[test]
+    fn keeps_protected_sessions() {
+        let now = Utc.timestamp_opt(1_700_000_000, 0).single().unwrap();
+        let mut rules = rules();
+        rules.protected_patterns.push("prod-*".to_string());
+
+        let panes = [pane("zsh", "/")];
+        let mut flagged = session("db", now - Duration::days(9), false);
+        flagged.options.protected = true;
+
+        assert_eq!(rules.classify(&flagged, &panes, now), None);
+        assert_eq!(rules.classify(&session("prod-api", now - Duration::days(9), false), &panes, now), None);
+    }
+}
@@ -0,0 +1,231 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::config::{matches_any_pattern, RepoScanSettings};
+use crate::core::directories;
+
+/// A git repository found under one of the scan roots
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FoundRepo {
+    /// Path relative to its scan root, e.g. `org/app`
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// What a directory held when it was last read; valid while its mtime is unchanged,
+/// since adding or removing an entry (including `.git`) updates the directory's mtime
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedDir {
+    modified: SystemTime,
+    is_repo: bool,
+    children: Vec<PathBuf>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ScanCache {
+    dirs: HashMap<String, CachedDir>,
+}
+
+/// Walks the configured roots for git repositories, reusing cached directory
+/// listings so rescanning thousands of directories costs one `stat` each
+#[derive(Debug, Clone)]
+pub struct RepoScanner {
+    roots: Vec<PathBuf>,
+    max_depth: usize,
+    ignore: Vec<String>,
+    cache_file: Option<PathBuf>,
+}
+
+impl RepoScanner {
+    pub fn new(settings: &RepoScanSettings) -> Self {
+        let cache_file = dirs::cache_dir().map(|dir| dir.join("tmux-session-manager").join("repos.json"));
+
+        Self {
+            roots: settings.roots.iter().flatten().map(|root| directories::expand_home(root)).collect(),
+            max_depth: settings.max_depth,
+            ignore: settings.ignore.clone(),
+            cache_file,
+        }
+    }
+
+    pub fn with_cache_file(mut self, cache_file: Option<PathBuf>) -> Self {
+        self.cache_file = cache_file;
+        self
+    }
+
+    pub fn has_roots(&self) -> bool {
+        !self.roots.is_empty()
+    }
+
+    /// Repositories under the roots, sorted by name; the first root wins when names collide
+    pub async fn scan(&self) -> Vec<FoundRepo> {
+        if !self.has_roots() {
+            return Vec::new();
+        }
+
+        let scanner = self.clone();
+        match tokio::task::spawn_blocking(move || scanner.scan_blocking()).await {
+            Ok(repos) => repos,
+            Err(e) => {
+                eprintln!("Repository scan failed: {}", e);
+                Vec::new()
+            }
+        }
+    }
+
+    fn scan_blocking(&self) -> Vec<FoundRepo> {
+        let cache = self.load_cache();
+        let mut fresh = ScanCache::default();
+        let mut repos: Vec<FoundRepo> = Vec::new();
+
+        for root in &self.roots {
+            let mut pending = vec![(root.clone(), 0)];
+
+            while let Some((dir, depth)) = pending.pop() {
+                let Some(listing) = Self::read_dir_cached(&dir, &cache) else {
+                    continue;
+                };
+                fresh.dirs.insert(dir.to_string_lossy().to_string(), listing.clone());
+
+                // Repositories are not descended into; nested checkouts are submodules or vendored.
+                // Names with whitespace are left out, as picker entries are parsed back by token.
+                if listing.is_repo {
+                    let name = Self::repo_name(root, &dir);
+                    if !name.contains(char::is_whitespace) && !repos.iter().any(|repo| repo.name == name) {
+                        repos.push(FoundRepo { name, path: dir });
+                    }
+                    continue;
+                }
+
+                if depth < self.max_depth {
+                    pending.extend(
+                        listing
+                            .children
+                            .into_iter()
+                            .filter(|child| !self.is_ignored(child))
+                            .map(|child| (child, depth + 1)),
+                    );
+                }
+            }
+        }
+
+        self.save_cache(&fresh);
+
+        repos.sort_by(|a, b| a.name.cmp(&b.name));
+        repos
+    }
+
+    fn read_dir_cached(dir: &Path, cache: &ScanCache) -> Option<CachedDir> {
+        let modified = std::fs::metadata(dir).ok()?.modified().ok()?;
+
+        if let Some(cached) = cache.dirs.get(dir.to_string_lossy().as_ref()) {
+            if cached.modified == modified {
+                return Some(cached.clone());
+            }
+        }
+
+        let mut is_repo = false;
+        let mut children = Vec::new();
+        for entry in std::fs::read_dir(dir).ok()?.flatten() {
+            let file_name = entry.file_name();
+            let file_name = file_name.to_string_lossy();
+
+            if file_name == ".git" {
+                is_repo = true;
+            } else if !file_name.starts_with('.') && entry.file_type().map(|kind| kind.is_dir()).unwrap_or(false) {
+                children.push(entry.path());
+            }
+        }
+        children.sort();
+
+        Some(CachedDir { modified, is_repo, children })
+    }
+
+    fn is_ignored(&self, dir: &Path) -> bool {
+        dir.file_name()
+            .map(|name| matches_any_pattern(&self.ignore, &name.to_string_lossy()))
+            .unwrap_or(false)
+    }
+
+    fn repo_name(root: &Path, dir: &Path) -> String {
+        match dir.strip_prefix(root) {
+            Ok(relative) if !relative.as_os_str().is_empty() => relative.to_string_lossy().to_string(),
+            // A root that is itself a repository goes by its directory name
+            _ => dir
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| dir.to_string_lossy().to_string()),
+        }
+    }
+
+    fn load_cache(&self) -> ScanCache {
+        self.cache_file
+            .as_ref()
+            .and_then(|file| std::fs::read_to_string(file).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Best effort: an unwritable cache only means the next scan reads every directory again
+    fn save_cache(&self, cache: &ScanCache) {
+        let Some(file) = &self.cache_file else {
+            return;
+        };
+        if let Some(parent) = file.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Ok(content) = serde_json::to_string(cache) {
+            let _ = std::fs::write(file, content);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scanner(root: &Path, cache_file: &Path) -> RepoScanner {
+        let settings = RepoScanSettings {
+            roots: Some(vec![root.display().to_string()]),
+            max_depth: 2,
+            ignore: vec!["node_modules".to_string()],
+        };
+        RepoScanner::new(&settings).with_cache_file(Some(cache_file.to_path_buf()))
+    }
+
+    fn names(repos: &[FoundRepo]) -> Vec<&str> {
+        repos.iter().map(|repo| repo.name.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn finds_repos_within_depth_skipping_ignored_dirs() {
+        let root = tempfile::tempdir().unwrap();
+        let cache = tempfile::tempdir().unwrap();
+        for dir in ["api/.git", "api/vendor/lib/.git", "org/web/.git", "org/team/deep/.git", "node_modules/pkg/.git", ".hidden/.git", "my app/.git"] {
+            std::fs::create_dir_all(root.path().join(dir)).unwrap();
+        }
+
+        let repos = scanner(root.path(), &cache.path().join("repos.json")).scan().await;
+
+        assert_eq!(names(&repos), vec!["api", "org/web"]);
+        assert_eq!(repos[1].path, root.path().join("org/web"));
+    }
+
+    #[tokio::test]
+    async fn rescans_directories_whose_mtime_changed() {
+        let root = tempfile::tempdir().unwrap();
+        let cache = tempfile::tempdir().unwrap();
+        let cache_file = cache.path().join("repos.json");
+        std::fs::create_dir_all(root.path().join("api/.git")).unwrap();
+
+        let scanner = scanner(root.path(), &cache_file);
+        assert_eq!(names(&scanner.scan().await), vec!["api"]);
+        assert!(cache_file.exists());
+
+        // Adding a checkout touches the root, so the cached listing is not reused
+        std::fs::create_dir_all(root.path().join("cli/.git")).unwrap();
+        assert_eq!(names(&scanner.scan().await), vec!["api", "cli"]);
+    }
+}
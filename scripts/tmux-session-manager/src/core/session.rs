@@ -1,12 +1,50 @@
+use anyhow::{anyhow, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::PathBuf;
 use uuid::Uuid;
 
 pub fn is_floating_session(name: &str) -> bool {
     name.contains("scratch") || name == "assistant-terminal"
 }
 
+/// Human readable age such as "3d 4h" or "12m"
+pub fn format_age(age: chrono::Duration) -> String {
+    let minutes = age.num_minutes().max(0);
+    let (days, hours, minutes) = (minutes / 1440, (minutes % 1440) / 60, minutes % 60);
+
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+/// Commands that count as an idle prompt rather than running work
+const SHELL_COMMANDS: &[&str] = &["bash", "zsh", "fish", "sh", "dash", "ksh", "tcsh", "csh", "nu", "login"];
+
+pub fn is_shell_command(command: &str) -> bool {
+    // Login shells are reported with a leading dash (e.g. "-zsh")
+    SHELL_COMMANDS.contains(&command.trim_start_matches('-'))
+}
+
+/// Reject names tmux would silently rewrite or fail to target
+pub fn validate_session_name(name: &str) -> Result<()> {
+    if name.trim().is_empty() {
+        return Err(anyhow!("Session name cannot be empty"));
+    }
+
+    // tmux uses ':' and '.' as target separators (session:window.pane)
+    if let Some(illegal) = name.chars().find(|c| matches!(c, ':' | '.') || c.is_control()) {
+        return Err(anyhow!("Session name cannot contain {:?}", illegal));
+    }
+
+    Ok(())
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionItem {
     pub id: Uuid,
@@ -16,6 +54,15 @@ pub struct SessionItem {
     pub timestamp: DateTime<Utc>,
     pub is_current: bool,
     pub is_active: bool,
+    pub is_pinned: bool,
+    pub is_protected: bool,
+    pub tags: Vec<String>,
+    /// Ranking within the plugin's priority (e.g. zoxide frecency); higher sorts first
+    pub score: Option<f64>,
+    /// Entries of one plugin sharing a group (e.g. worktrees of one repository) are listed together
+    pub group: Option<String>,
+    /// Plugin-provided status badges (e.g. a worktree's git state), already colored
+    pub status: Option<String>,
     pub metadata: SessionMetadata,
 }
 
@@ -33,6 +80,8 @@ pub struct SessionContext {
     pub active_sessions: Vec<String>,
     pub scratch_sessions: Vec<String>,
     pub all_tmux_sessions: Vec<TmuxSession>,
+    /// Directory the manager was started in, standing in for the current session's outside tmux
+    pub working_dir: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,6 +91,82 @@ pub struct TmuxSession {
     pub windows: u32,
     pub attached: bool,
     pub current_path: Option<String>,
+    #[serde(default)]
+    pub options: SessionOptions,
+}
+
+/// State this tool keeps on sessions as tmux user options (`@tsm_*`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionOptions {
+    pub protected: bool,
+    /// Directory the session belongs to, kept when the session is renamed
+    pub path: Option<String>,
+    /// Original project or branch name the session was created for, before sanitizing
+    pub identity: Option<String>,
+    /// Plugin that created the session; `None` for sessions made outside this tool
+    pub plugin: Option<String>,
+    pub project: Option<String>,
+    pub created: Option<DateTime<Utc>>,
+}
+
+impl SessionOptions {
+    /// `Some(true)` when stamped by `plugin`, `Some(false)` when stamped by another, `None` if unstamped
+    pub fn owned_by(&self, plugin: &str) -> Option<bool> {
+        self.plugin.as_deref().map(|owner| owner == plugin)
+    }
+}
+
+/// Ownership recorded as user options when a plugin creates a session
+#[derive(Debug, Clone)]
+pub struct SessionStamp {
+    pub plugin: String,
+    pub path: Option<String>,
+    pub project: Option<String>,
+    pub identity: Option<String>,
+}
+
+impl SessionStamp {
+    pub fn new(plugin: &str) -> Self {
+        Self {
+            plugin: plugin.to_string(),
+            path: None,
+            project: None,
+            identity: None,
+        }
+    }
+
+    pub fn with_path(mut self, path: &str) -> Self {
+        self.path = Some(path.to_string());
+        self
+    }
+
+    pub fn with_project(mut self, project: &str) -> Self {
+        self.project = Some(project.to_string());
+        self
+    }
+
+    pub fn with_identity(mut self, identity: &str) -> Self {
+        self.identity = Some(identity.to_string());
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TmuxPane {
+    pub window_index: u32,
+    pub window_name: String,
+    pub window_layout: String,
+    pub window_active: bool,
+    pub pane_index: u32,
+    pub pane_active: bool,
+    pub current_command: String,
+    pub current_path: String,
+}
+
+impl TmuxPane {
+    pub fn is_busy(&self) -> bool {
+        !is_shell_command(&self.current_command)
+    }
 }
 
 impl SessionItem {
@@ -59,6 +184,12 @@ impl SessionItem {
             timestamp: Utc::now(),
             is_current: false,
             is_active: false,
+            is_pinned: false,
+            is_protected: false,
+            tags: Vec::new(),
+            score: None,
+            group: None,
+            status: None,
             metadata,
         }
     }
@@ -78,12 +209,65 @@ impl SessionItem {
         self
     }
 
+    pub fn with_score(mut self, score: f64) -> Self {
+        self.score = Some(score);
+        self
+    }
+
+    pub fn with_group(mut self, group: String) -> Self {
+        self.group = Some(group);
+        self
+    }
+
+    /// Empty statuses are dropped so they don't leave a gap on the line
+    pub fn with_status(mut self, status: String) -> Self {
+        self.status = Some(status).filter(|status| !status.is_empty());
+        self
+    }
+
+    pub fn with_pinned(mut self, is_pinned: bool) -> Self {
+        self.is_pinned = is_pinned;
+        self
+    }
+
+    pub fn with_protected(mut self, is_protected: bool) -> Self {
+        self.is_protected = is_protected;
+        self
+    }
+
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
     pub fn format_for_display(&self) -> String {
         let icon = self.get_display_icon();
         let color = self.get_display_color();
+        let badges = self.get_display_badges();
         let suffix = self.get_display_suffix();
 
-        format!("{}{}\x1b[0m {}{}", color, icon, self.name, suffix)
+        format!("{}{}\x1b[0m {}{}{}", color, icon, self.name, badges, suffix)
+    }
+
+    /// Protection, pin, tag and status badges shown after the name
+    fn get_display_badges(&self) -> String {
+        let mut badges = String::new();
+
+        if self.is_protected {
+            badges.push_str(" \x1b[1;31m\u{f023}\x1b[0m");
+        }
+        if self.is_pinned {
+            badges.push_str(" \x1b[1;35m\u{f435}\x1b[0m");
+        }
+        for tag in &self.tags {
+            badges.push_str(&format!(" \x1b[0;36m#{}\x1b[0m", tag));
+        }
+        if let Some(status) = &self.status {
+            badges.push(' ');
+            badges.push_str(status);
+        }
+
+        badges
     }
 
     fn get_display_icon(&self) -> &str {
@@ -93,7 +277,9 @@ impl SessionItem {
             match self.plugin_name.as_str() {
                 "recent" => "★",
                 "active" => "●",
-                "worktree" => if self.is_active { "●" } else { "○" },
+                "worktree" | "jj" => if self.is_active { "●" } else { "○" },
+                "project" => if self.is_active { "●" } else { "○" },
+                "repo" | "zoxide" => "○",
                 "scratch" if self.name == "assistant-terminal" => "󰚩",
                 "scratch" => "󱗽",
                 "tmuxinator" => "●",
@@ -107,6 +293,9 @@ impl SessionItem {
             "recent" => "\x1b[1;33m", // YELLOW (like the bash version)
             "active" => "\x1b[1;32m", // GREEN
             "worktree" => "\x1b[0;34m", // BLUE
+            "project" | "jj" => "\x1b[0;35m", // MAGENTA
+            "repo" => "\x1b[0;36m", // CYAN
+            "zoxide" => "\x1b[0;33m", // DARK YELLOW
             "scratch" => if self.is_active { "\x1b[1;32m" } else { "\x1b[0;34m" }, // GREEN if active, BLUE if not
             "tmuxinator" => "\x1b[0;90m", // DARK_GREY
             _ => "\x1b[1;32m", // Default GREEN
@@ -153,6 +342,12 @@ impl SessionMetadata {
     }
 }
 
+impl Default for SessionContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl SessionContext {
     pub fn new() -> Self {
         Self {
@@ -160,6 +355,7 @@ impl SessionContext {
             active_sessions: Vec::new(),
             scratch_sessions: Vec::new(),
             all_tmux_sessions: Vec::new(),
+            working_dir: None,
         }
     }
 
@@ -182,4 +378,9 @@ impl SessionContext {
         self.all_tmux_sessions = sessions;
         self
     }
+
+    pub fn with_working_dir(mut self, dir: Option<PathBuf>) -> Self {
+        self.working_dir = dir;
+        self
+    }
 }
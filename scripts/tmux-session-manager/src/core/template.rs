@@ -0,0 +1,49 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::core::tmux::TmuxClient;
+
+/// Initial windows for a new session, configured under `templates`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub windows: Vec<TemplateWindow>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateWindow {
+    pub name: String,
+    /// Typed into the window's shell, so the shell stays once the command exits
+    #[serde(default)]
+    pub command: Option<String>,
+}
+
+impl SessionTemplate {
+    /// Shape a freshly created session: the first window is reused, the rest are added after it
+    pub async fn apply(&self, tmux: &TmuxClient, session_name: &str, path: &str) -> Result<()> {
+        let mut first_window = None;
+
+        for window in &self.windows {
+            let window_id = match &first_window {
+                None => {
+                    let window_id = tmux.first_window_id(session_name).await?;
+                    tmux.rename_window(&window_id, &window.name).await?;
+                    first_window = Some(window_id.clone());
+                    window_id
+                }
+                Some(_) => tmux.new_window(session_name, &window.name, Some(path), None).await?,
+            };
+
+            if let Some(command) = &window.command {
+                tmux.send_keys(&window_id, command).await?;
+            }
+        }
+
+        if let Some(window_id) = first_window {
+            tmux.select_window(&window_id).await?;
+        }
+
+        Ok(())
+    }
+}
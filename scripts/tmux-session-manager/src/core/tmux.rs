@@ -2,17 +2,53 @@ use anyhow::{anyhow, Context, Result};
 use chrono::{TimeZone, Utc};
 use tokio::process::Command;
 
-use crate::core::session::{is_floating_session, SessionContext, TmuxSession};
+use crate::core::session::{is_floating_session, SessionContext, SessionOptions, SessionStamp, TmuxPane, TmuxSession};
+
+/// Base session fields are colon separated; the active pane's path and the user options follow
+/// as tab-separated fields so their values may contain colons
+const SESSION_FORMAT: &str = "#{session_last_attached}:#{session_name}:#{session_windows}:#{session_attached}\t#{pane_current_path}\t#{@tsm_protected}\t#{@tsm_path}\t#{@tsm_identity}\t#{@tsm_plugin}\t#{@tsm_project}\t#{@tsm_created}";
+
+pub const PROTECTED_OPTION: &str = "@tsm_protected";
+pub const PATH_OPTION: &str = "@tsm_path";
+pub const IDENTITY_OPTION: &str = "@tsm_identity";
+pub const PLUGIN_OPTION: &str = "@tsm_plugin";
+pub const PROJECT_OPTION: &str = "@tsm_project";
+pub const CREATED_OPTION: &str = "@tsm_created";
+
+const PANE_FORMAT: &str = "#{window_index}\t#{window_name}\t#{window_layout}\t#{window_active}\t#{pane_index}\t#{pane_active}\t#{pane_current_command}\t#{pane_current_path}";
+
+/// Quote a string for safe interpolation into a shell command line
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Target naming exactly one session; tmux otherwise also takes a bare name as a prefix
+/// (`app` reaching `apple`) or a pattern
+fn exact_session(session_name: &str) -> String {
+    format!("={}", session_name)
+}
 
 pub struct TmuxClient {
     socket_path: Option<String>,
 }
 
+impl Default for TmuxClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl TmuxClient {
     pub fn new() -> Self {
         Self { socket_path: None }
     }
 
+    /// Talk to the server listening on `socket_path` instead of the default one
+    pub fn with_socket(mut self, socket_path: &str) -> Self {
+        self.socket_path = Some(socket_path.to_string());
+        self
+    }
+
 
     pub async fn get_session_context(&self) -> Result<SessionContext> {
         let all_sessions = self.list_all_sessions().await?;
@@ -24,15 +60,16 @@ impl TmuxClient {
             .with_current_session(current_session)
             .with_active_sessions(active_sessions)
             .with_scratch_sessions(scratch_sessions)
-            .with_all_tmux_sessions(all_sessions))
+            .with_all_tmux_sessions(all_sessions)
+            .with_working_dir(std::env::current_dir().ok()))
     }
 
-    async fn list_all_sessions(&self) -> Result<Vec<TmuxSession>> {
+    pub async fn list_all_sessions(&self) -> Result<Vec<TmuxSession>> {
         let output = self
             .execute_tmux_command(&[
                 "list-sessions",
                 "-F",
-                "#{session_last_attached}:#{session_name}:#{session_windows}:#{session_attached}",
+                SESSION_FORMAT,
             ])
             .await?;
 
@@ -44,7 +81,7 @@ impl TmuxClient {
         }
 
         // Sort by last attached time (most recent first)
-        sessions.sort_by(|a, b| b.last_attached.cmp(&a.last_attached));
+        sessions.sort_by_key(|session| std::cmp::Reverse(session.last_attached));
 
         Ok(sessions)
     }
@@ -75,7 +112,7 @@ impl TmuxClient {
     }
 
     pub async fn has_session(&self, session_name: &str) -> bool {
-        self.execute_tmux_command(&["has-session", "-t", session_name])
+        self.execute_tmux_command(&["has-session", "-t", &exact_session(session_name)])
             .await
             .is_ok()
     }
@@ -87,11 +124,33 @@ impl TmuxClient {
     }
 
     pub async fn attach_session(&self, session_name: &str) -> Result<()> {
-        self.execute_tmux_command(&["attach-session", "-t", session_name])
-            .await?;
+        // Attaching takes over the terminal, so the client needs our stdio rather than pipes
+        let mut cmd = Command::new("tmux");
+        if let Some(socket) = &self.socket_path {
+            cmd.args(["-S", socket]);
+        }
+
+        let status = cmd
+            .args(["attach-session", "-t", session_name])
+            .status()
+            .await
+            .context("Failed to execute tmux command")?;
+
+        if !status.success() {
+            return Err(anyhow!("tmux attach-session failed for: {}", session_name));
+        }
         Ok(())
     }
 
+    /// Switch the current client inside tmux, attach otherwise
+    pub async fn switch_or_attach(&self, session_name: &str) -> Result<()> {
+        if Self::is_inside_tmux() {
+            self.switch_client(session_name).await
+        } else {
+            self.attach_session(session_name).await
+        }
+    }
+
     pub async fn new_session(&self, session_name: &str, path: Option<&str>) -> Result<()> {
         let mut args = vec!["new-session", "-d", "-s", session_name];
 
@@ -103,8 +162,45 @@ impl TmuxClient {
         Ok(())
     }
 
+    pub async fn set_session_option(&self, session_name: &str, option: &str, value: &str) -> Result<()> {
+        self.execute_tmux_command(&["set-option", "-t", session_name, option, value])
+            .await?;
+        Ok(())
+    }
+
+    /// Record which plugin created a session, in a single tmux invocation
+    pub async fn stamp_session(&self, session_name: &str, stamp: &SessionStamp) -> Result<()> {
+        let created = Utc::now().timestamp().to_string();
+        let options = [
+            (PLUGIN_OPTION, Some(stamp.plugin.as_str())),
+            (CREATED_OPTION, Some(created.as_str())),
+            (PATH_OPTION, stamp.path.as_deref()),
+            (PROJECT_OPTION, stamp.project.as_deref()),
+            (IDENTITY_OPTION, stamp.identity.as_deref()),
+        ];
+
+        let mut args = Vec::new();
+        for (option, value) in options {
+            if let Some(value) = value {
+                if !args.is_empty() {
+                    args.push(";");
+                }
+                args.extend_from_slice(&["set-option", "-t", session_name, option, value]);
+            }
+        }
+
+        self.execute_tmux_command(&args).await?;
+        Ok(())
+    }
+
+    pub async fn unset_session_option(&self, session_name: &str, option: &str) -> Result<()> {
+        self.execute_tmux_command(&["set-option", "-u", "-t", session_name, option])
+            .await?;
+        Ok(())
+    }
+
     pub async fn kill_session(&self, session_name: &str) -> Result<()> {
-        self.execute_tmux_command(&["kill-session", "-t", session_name])
+        self.execute_tmux_command(&["kill-session", "-t", &exact_session(session_name)])
             .await?;
         Ok(())
     }
@@ -156,6 +252,106 @@ impl TmuxClient {
         Ok(content)
     }
 
+    /// Capture the full scrollback of a pane, including escape sequences
+    pub async fn capture_pane_history(&self, target: &str) -> Result<String> {
+        self.execute_tmux_command(&["capture-pane", "-p", "-e", "-J", "-S", "-", "-t", target])
+            .await
+    }
+
+    pub async fn list_panes(&self, session_name: &str) -> Result<Vec<TmuxPane>> {
+        let output = self
+            .execute_tmux_command(&["list-panes", "-s", "-t", session_name, "-F", PANE_FORMAT])
+            .await?;
+
+        Ok(output.lines().filter_map(|line| self.parse_pane_line(line)).collect())
+    }
+
+    /// Return the id of the first window of a session (e.g. "@3")
+    pub async fn first_window_id(&self, session_name: &str) -> Result<String> {
+        let output = self
+            .execute_tmux_command(&["list-windows", "-t", session_name, "-F", "#{window_id}"])
+            .await?;
+
+        output.lines().next()
+            .map(|id| id.to_string())
+            .ok_or_else(|| anyhow!("No window found in session: {}", session_name))
+    }
+
+    /// Create a detached window at the end of a session and return its id
+    pub async fn new_window(&self, session_name: &str, window_name: &str, path: Option<&str>, command: Option<&str>) -> Result<String> {
+        let target = format!("{}:", session_name);
+        let mut args = vec!["new-window", "-d", "-P", "-F", "#{window_id}", "-t", &target, "-n", window_name];
+
+        if let Some(path) = path {
+            args.extend_from_slice(&["-c", path]);
+        }
+        if let Some(command) = command {
+            args.push(command);
+        }
+
+        let output = self.execute_tmux_command(&args).await?;
+        Ok(output.trim().to_string())
+    }
+
+    /// Split a pane without selecting the new one and return its id (e.g. "%7")
+    pub async fn split_window(&self, target: &str, path: Option<&str>, command: Option<&str>) -> Result<String> {
+        let mut args = vec!["split-window", "-d", "-P", "-F", "#{pane_id}", "-t", target];
+
+        if let Some(path) = path {
+            args.extend_from_slice(&["-c", path]);
+        }
+        if let Some(command) = command {
+            args.push(command);
+        }
+
+        let output = self.execute_tmux_command(&args).await?;
+        Ok(output.trim().to_string())
+    }
+
+    /// Replace the process running in a pane
+    pub async fn respawn_pane(&self, target: &str, path: Option<&str>, command: &str) -> Result<()> {
+        let mut args = vec!["respawn-pane", "-k", "-t", target];
+
+        if let Some(path) = path {
+            args.extend_from_slice(&["-c", path]);
+        }
+        args.push(command);
+
+        self.execute_tmux_command(&args).await?;
+        Ok(())
+    }
+
+    pub async fn rename_window(&self, target: &str, window_name: &str) -> Result<()> {
+        self.execute_tmux_command(&["rename-window", "-t", target, window_name])
+            .await?;
+        Ok(())
+    }
+
+    /// Type a command into a pane and press Enter, leaving the shell running afterwards
+    pub async fn send_keys(&self, target: &str, command: &str) -> Result<()> {
+        self.execute_tmux_command(&["send-keys", "-t", target, "-l", command, ";", "send-keys", "-t", target, "Enter"])
+            .await?;
+        Ok(())
+    }
+
+    pub async fn select_layout(&self, target: &str, layout: &str) -> Result<()> {
+        self.execute_tmux_command(&["select-layout", "-t", target, layout])
+            .await?;
+        Ok(())
+    }
+
+    pub async fn select_window(&self, target: &str) -> Result<()> {
+        self.execute_tmux_command(&["select-window", "-t", target])
+            .await?;
+        Ok(())
+    }
+
+    pub async fn select_pane(&self, target: &str) -> Result<()> {
+        self.execute_tmux_command(&["select-pane", "-t", target])
+            .await?;
+        Ok(())
+    }
+
     pub async fn get_session_info(&self, session_name: &str) -> Result<(u32, bool)> {
         let output = self
             .execute_tmux_command(&[
@@ -184,7 +380,7 @@ impl TmuxClient {
         let mut cmd = Command::new("tmux");
 
         if let Some(socket) = &self.socket_path {
-            cmd.args(&["-S", socket]);
+            cmd.args(["-S", socket]);
         }
 
         cmd.args(args);
@@ -203,6 +399,8 @@ impl TmuxClient {
     }
 
     fn parse_session_line(&self, line: &str) -> Result<Option<TmuxSession>> {
+        let (line, extra) = line.split_once('\t').unwrap_or((line, ""));
+        let (current_path, options) = extra.split_once('\t').unwrap_or((extra, ""));
         let parts: Vec<&str> = line.split(':').collect();
         if parts.len() < 4 {
             return Ok(None);
@@ -228,10 +426,47 @@ impl TmuxClient {
             last_attached,
             windows,
             attached,
-            current_path: None, // Will be populated separately if needed
+            current_path: Some(current_path).filter(|path| !path.is_empty()).map(str::to_string),
+            options: self.parse_session_options(options),
         }))
     }
 
+    fn parse_session_options(&self, options: &str) -> SessionOptions {
+        let values: Vec<&str> = options.split('\t').collect();
+        let value = |index: usize| values.get(index).copied().unwrap_or("");
+        let text = |index: usize| Some(value(index)).filter(|value| !value.is_empty()).map(str::to_string);
+
+        SessionOptions {
+            protected: value(0) == "1",
+            path: text(1),
+            identity: text(2),
+            plugin: text(3),
+            project: text(4),
+            created: value(5)
+                .parse::<i64>()
+                .ok()
+                .and_then(|timestamp| Utc.timestamp_opt(timestamp, 0).single()),
+        }
+    }
+
+    fn parse_pane_line(&self, line: &str) -> Option<TmuxPane> {
+        let parts: Vec<&str> = line.splitn(8, '\t').collect();
+        if parts.len() < 8 {
+            return None;
+        }
+
+        Some(TmuxPane {
+            window_index: parts[0].parse().ok()?,
+            window_name: parts[1].to_string(),
+            window_layout: parts[2].to_string(),
+            window_active: parts[3] == "1",
+            pane_index: parts[4].parse().ok()?,
+            pane_active: parts[5] == "1",
+            current_command: parts[6].to_string(),
+            current_path: parts[7].to_string(),
+        })
+    }
+
     fn categorize_sessions(&self, sessions: &[TmuxSession], current_session: &Option<String>) -> (Vec<String>, Vec<String>) {
         let mut active_sessions = Vec::new();
         let mut scratch_sessions = Vec::new();
@@ -298,4 +533,72 @@ mod tests {
 
         assert_eq!(session.last_attached.timestamp(), 0);
     }
+
+    #[test]
+    fn parses_session_user_options() {
+        let session = TmuxClient::new()
+            .parse_session_line("1234567890:infra:3:1\t/srv/infra/web\t1\t/srv/infra:prod\tinfra.prod\ttmuxinator\tinfra\t1700000000")
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(session.name, "infra");
+        assert_eq!(session.current_path.as_deref(), Some("/srv/infra/web"));
+        assert!(session.options.protected);
+        assert_eq!(session.options.path.as_deref(), Some("/srv/infra:prod"));
+        assert_eq!(session.options.identity.as_deref(), Some("infra.prod"));
+        assert_eq!(session.options.owned_by("tmuxinator"), Some(true));
+        assert_eq!(session.options.project.as_deref(), Some("infra"));
+        assert_eq!(session.options.created.map(|created| created.timestamp()), Some(1_700_000_000));
+    }
+
+    #[test]
+    fn parses_pane_line() {
+        let pane = TmuxClient::new()
+            .parse_pane_line("2\teditor\tb25d,80x24,0,0,1\t1\t0\t1\tnvim\t/home/me/src/app")
+            .unwrap();
+
+        assert_eq!(pane.window_index, 2);
+        assert_eq!(pane.window_name, "editor");
+        assert!(pane.window_active);
+        assert_eq!(pane.current_command, "nvim");
+        assert_eq!(pane.current_path, "/home/me/src/app");
+        assert!(pane.is_busy());
+    }
+
+    #[test]
+    fn login_shell_pane_is_not_busy() {
+        let pane = TmuxClient::new()
+            .parse_pane_line("1\tzsh\tb25d,80x24,0,0,1\t1\t0\t1\t-zsh\t/home/me")
+            .unwrap();
+
+        assert!(!pane.is_busy());
+    }
+
+    #[tokio::test]
+    async fn session_lookups_match_names_exactly() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("tmux.sock").to_string_lossy().to_string();
+        let started = std::process::Command::new("tmux")
+            .args(["-S", &socket, "-f", "/dev/null", "new-session", "-d", "-s", "apple"])
+            .status()
+            .is_ok_and(|status| status.success());
+        if !started {
+            return;
+        }
+        let tmux = TmuxClient::new().with_socket(&socket);
+
+        let missing = tmux.has_session("app").await;
+        let killed = tmux.kill_session("app").await.is_ok();
+        let survived = tmux.has_session("apple").await;
+        let _ = tmux.kill_session("apple").await;
+
+        assert!(!missing);
+        assert!(!killed);
+        assert!(survived);
+    }
+
+    #[test]
+    fn quotes_single_quotes_for_the_shell() {
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+    }
 }
@@ -0,0 +1,281 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::core::tmux::{shell_quote, TmuxClient};
+
+/// Number of killed sessions kept around for undo
+const DEFAULT_CAPACITY: usize = 20;
+const SNAPSHOT_FILE: &str = "session.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashedSession {
+    pub name: String,
+    pub killed_at: DateTime<Utc>,
+    pub windows: Vec<TrashedWindow>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashedWindow {
+    pub index: u32,
+    pub name: String,
+    pub layout: String,
+    pub active: bool,
+    pub panes: Vec<TrashedPane>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashedPane {
+    pub path: String,
+    pub command: String,
+    pub scrollback_file: String,
+}
+
+/// Outcome of restoring several trashed sessions
+#[derive(Debug, Default)]
+pub struct RestoreReport {
+    pub restored: Vec<String>,
+    pub failed: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TrashEntry {
+    pub dir: PathBuf,
+    pub session: TrashedSession,
+}
+
+/// Snapshots of killed sessions (layout and scrollback) so they can be recreated
+pub struct TrashBin {
+    dir: PathBuf,
+    capacity: usize,
+}
+
+impl Default for TrashBin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TrashBin {
+    pub fn new() -> Self {
+        let dir = dirs::data_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("tmux-session-manager")
+            .join("trash");
+
+        Self::with_dir(dir)
+    }
+
+    pub fn with_dir(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            capacity: DEFAULT_CAPACITY,
+        }
+    }
+
+    /// Record the layout and scrollback of a running session before it is killed
+    pub async fn snapshot(&self, tmux: &TmuxClient, session_name: &str) -> Result<TrashEntry> {
+        let panes = tmux.list_panes(session_name).await?;
+        let killed_at = Utc::now();
+
+        let entry_dir = self.dir.join(format!(
+            "{}-{}",
+            killed_at.timestamp_millis(),
+            session_name.replace(['/', ' '], "_")
+        ));
+        tokio::fs::create_dir_all(&entry_dir).await
+            .with_context(|| format!("Failed to create trash entry: {}", entry_dir.display()))?;
+
+        let mut windows: Vec<TrashedWindow> = Vec::new();
+        for pane in &panes {
+            let target = format!("{}:{}.{}", session_name, pane.window_index, pane.pane_index);
+            let scrollback = tmux.capture_pane_history(&target).await.unwrap_or_default();
+            let scrollback_file = entry_dir.join(format!("pane-{}-{}.txt", pane.window_index, pane.pane_index));
+            tokio::fs::write(&scrollback_file, scrollback).await?;
+
+            let trashed_pane = TrashedPane {
+                path: pane.current_path.clone(),
+                command: pane.current_command.clone(),
+                scrollback_file: scrollback_file.to_string_lossy().to_string(),
+            };
+
+            // Panes are listed window by window, so a new window starts whenever the index changes
+            match windows.last_mut() {
+                Some(window) if window.index == pane.window_index => {
+                    window.panes.push(trashed_pane);
+                }
+                _ => windows.push(TrashedWindow {
+                    index: pane.window_index,
+                    name: pane.window_name.clone(),
+                    layout: pane.window_layout.clone(),
+                    active: pane.window_active,
+                    panes: vec![trashed_pane],
+                }),
+            }
+        }
+
+        let session = TrashedSession {
+            name: session_name.to_string(),
+            killed_at,
+            windows,
+        };
+
+        let content = serde_json::to_string_pretty(&session)?;
+        tokio::fs::write(entry_dir.join(SNAPSHOT_FILE), content).await?;
+
+        self.trim().await?;
+
+        Ok(TrashEntry {
+            dir: entry_dir,
+            session,
+        })
+    }
+
+    /// Drop a snapshot whose session was not killed after all
+    pub async fn discard(&self, entry: &TrashEntry) -> Result<()> {
+        tokio::fs::remove_dir_all(&entry.dir).await?;
+        Ok(())
+    }
+
+    /// List trashed sessions, most recently killed first
+    pub async fn list(&self) -> Result<Vec<TrashEntry>> {
+        let mut entries = Vec::new();
+
+        for dir in self.entry_dirs().await? {
+            let snapshot = dir.join(SNAPSHOT_FILE);
+            let Ok(content) = tokio::fs::read_to_string(&snapshot).await else {
+                continue; // Already restored or partially written
+            };
+
+            if let Ok(session) = serde_json::from_str::<TrashedSession>(&content) {
+                entries.push(TrashEntry { dir, session });
+            }
+        }
+
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.session.killed_at));
+        Ok(entries)
+    }
+
+    /// Recreate the `count` most recently killed sessions. One that fails to restore stays
+    /// in the trash bin and does not stop the others.
+    pub async fn restore_latest(&self, tmux: &TmuxClient, count: usize) -> Result<RestoreReport> {
+        let entries = self.list().await?;
+        if entries.is_empty() {
+            return Err(anyhow!("Trash bin is empty, nothing to restore"));
+        }
+
+        let mut report = RestoreReport::default();
+        for entry in entries.iter().take(count) {
+            match self.restore(tmux, entry).await {
+                Ok(session_name) => report.restored.push(session_name),
+                Err(e) => report.failed.push((entry.session.name.clone(), e.to_string())),
+            }
+        }
+
+        Ok(report)
+    }
+
+    pub async fn restore(&self, tmux: &TmuxClient, entry: &TrashEntry) -> Result<String> {
+        let session = &entry.session;
+
+        if tmux.has_session(&session.name).await {
+            return Err(anyhow!("Cannot restore '{}': a session with that name already exists", session.name));
+        }
+
+        // A half-built session would block the next attempt, so it goes and the snapshot stays
+        if let Err(e) = Self::rebuild(tmux, session).await {
+            let _ = tmux.kill_session(&session.name).await;
+            return Err(anyhow!("{} (the snapshot was kept)", e));
+        }
+
+        // Scrollback files stay behind for panes still replaying them; `trim` removes them later
+        tokio::fs::remove_file(entry.dir.join(SNAPSHOT_FILE)).await?;
+
+        Ok(session.name.clone())
+    }
+
+    /// Recreate a session's windows and panes, each pane replaying its scrollback
+    async fn rebuild(tmux: &TmuxClient, session: &TrashedSession) -> Result<()> {
+        let mut active_window = None;
+        for (index, window) in session.windows.iter().enumerate() {
+            let Some((first_pane, other_panes)) = window.panes.split_first() else {
+                continue;
+            };
+
+            let window_id = if index == 0 {
+                tmux.new_session(&session.name, Some(&first_pane.path)).await?;
+                let window_id = tmux.first_window_id(&session.name).await?;
+                tmux.respawn_pane(&window_id, Some(&first_pane.path), &Self::replay_command(first_pane)).await?;
+                tmux.rename_window(&window_id, &window.name).await?;
+                window_id
+            } else {
+                tmux.new_window(&session.name, &window.name, Some(&first_pane.path), Some(&Self::replay_command(first_pane))).await?
+            };
+
+            for pane in other_panes {
+                tmux.split_window(&window_id, Some(&pane.path), Some(&Self::replay_command(pane))).await?;
+            }
+
+            // Layouts can fail to apply when the client size differs; the panes are still there
+            let _ = tmux.select_layout(&window_id, &window.layout).await;
+
+            if window.active {
+                active_window = Some(window_id);
+            }
+        }
+
+        if let Some(window_id) = active_window {
+            let _ = tmux.select_window(&window_id).await;
+        }
+
+        Ok(())
+    }
+
+    /// Shell command that replays saved scrollback before handing over to the user's shell
+    fn replay_command(pane: &TrashedPane) -> String {
+        let file = shell_quote(&pane.scrollback_file);
+        let script = format!(r#"cat {} 2>/dev/null; exec "${{SHELL:-/bin/sh}}""#, file);
+        format!("sh -c {}", shell_quote(&script))
+    }
+
+    async fn entry_dirs(&self) -> Result<Vec<PathBuf>> {
+        let mut dirs = Vec::new();
+        if !self.dir.exists() {
+            return Ok(dirs);
+        }
+
+        let mut entries = tokio::fs::read_dir(&self.dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            if entry.file_type().await?.is_dir() {
+                dirs.push(entry.path());
+            }
+        }
+
+        // Entry directories are prefixed with the kill timestamp
+        dirs.sort_by_key(|dir| std::cmp::Reverse(Self::entry_timestamp(dir)));
+        Ok(dirs)
+    }
+
+    fn entry_timestamp(dir: &Path) -> i64 {
+        dir.file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.split('-').next())
+            .and_then(|timestamp| timestamp.parse().ok())
+            .unwrap_or(0)
+    }
+
+    /// Drop the oldest entries beyond capacity, and the scrollback left by restored ones
+    async fn trim(&self) -> Result<()> {
+        // A minute is plenty for restored panes to replay, and for a snapshot being written
+        let settled = Utc::now().timestamp_millis() - 60_000;
+
+        for (position, dir) in self.entry_dirs().await?.into_iter().enumerate() {
+            let restored = !dir.join(SNAPSHOT_FILE).exists() && Self::entry_timestamp(&dir) < settled;
+            if position >= self.capacity || restored {
+                tokio::fs::remove_dir_all(&dir).await?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,24 +1,92 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
 use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use tempfile::NamedTempFile;
 use tokio::process::Command;
 use tokio::io::AsyncWriteExt;
 
-use crate::core::tmux::TmuxClient;
+use crate::core::tmux::{shell_quote, TmuxClient, PROTECTED_OPTION};
+use crate::core::trash::TrashBin;
 use crate::plugins::PluginManager;
-use crate::core::session::SessionContext;
+use crate::core::prune::{format_table, PruneCandidate};
+use crate::core::session::{SessionContext, SessionItem, SessionMetadata, SessionStamp};
+use crate::core::template::SessionTemplate;
+use crate::config::WorktreeSettings;
+use crate::core::{directories, git, naming};
+
+/// First entry of the template stage, for a session with a single plain window
+const NO_TEMPLATE: &str = "(no template)";
+
+/// Separates the hidden session name field from what fzf displays, so rows map back to
+/// sessions whatever characters their names contain
+const NAME_DELIMITER: char = '\t';
+
+/// Keys that end the selector with an action instead of a plain switch
+const EXPECTED_KEYS: &str = "ctrl-x,ctrl-r,ctrl-s,ctrl-n,ctrl-p,ctrl-z,ctrl-t,alt-p,alt-x,alt-l,alt-w,alt-d,alt-r";
+
+/// Prefix of the worktree picker entries that create a branch rather than check one out
+const NEW_BRANCH_FROM: &str = "+ new branch from ";
 
 pub struct FzfInterface<'a> {
     pub plugin_manager: &'a PluginManager,
     preview_enabled: bool,
     force_no_popup: bool,
+    header: Option<String>,
+    pinned_sessions: Vec<String>,
+    session_tags: HashMap<String, Vec<String>>,
+    project_roots: Vec<String>,
+    templates: Vec<SessionTemplate>,
+    worktree_location: String,
+    worktree_naming: String,
 }
 
 #[derive(Debug, Clone)]
 pub struct FzfResult {
+    /// What was typed; used to open or create a session when nothing matched
+    pub query: String,
     pub key: Option<String>,
-    pub selection: Option<String>,
+    pub selections: Vec<String>,
+}
+
+/// A completed rename and any warning from the plugin that owns the session
+#[derive(Debug, Clone)]
+pub struct Renamed {
+    pub new_name: String,
+    pub warning: Option<String>,
+}
+
+/// Outcome of an action applied to several sessions at once
+#[derive(Debug, Default)]
+pub struct BulkReport {
+    pub succeeded: Vec<String>,
+    pub skipped: Vec<String>,
+    pub failed: Vec<(String, String)>,
+}
+
+impl BulkReport {
+    pub fn record(&mut self, session_name: &str, result: Result<bool>) {
+        match result {
+            Ok(true) => self.succeeded.push(session_name.to_string()),
+            Ok(false) => self.skipped.push(session_name.to_string()),
+            Err(e) => self.failed.push((session_name.to_string(), e.to_string())),
+        }
+    }
+
+    /// One line per outcome, suitable for the selector header
+    pub fn summary(&self, action: &str) -> String {
+        let mut lines = vec![format!("{} {} session(s)", action, self.succeeded.len())];
+
+        if !self.skipped.is_empty() {
+            lines.push(format!("Skipped: {}", self.skipped.join(", ")));
+        }
+        for (session_name, error) in &self.failed {
+            lines.push(format!("Failed {}: {}", session_name, error.trim()));
+        }
+
+        lines.join("\n")
+    }
 }
 
 impl<'a> FzfInterface<'a> {
@@ -27,6 +95,13 @@ impl<'a> FzfInterface<'a> {
             plugin_manager,
             preview_enabled: true, // Default enabled
             force_no_popup: false, // Default to use popup when inside tmux
+            header: None,
+            pinned_sessions: Vec::new(),
+            session_tags: HashMap::new(),
+            project_roots: Vec::new(),
+            templates: Vec::new(),
+            worktree_location: WorktreeSettings::default().location,
+            worktree_naming: WorktreeSettings::default().naming,
         }
     }
 
@@ -40,77 +115,162 @@ impl<'a> FzfInterface<'a> {
         self
     }
 
+    /// Status line shown above the session list (e.g. the outcome of the last action)
+    pub fn with_header(mut self, header: Option<String>) -> Self {
+        self.header = header;
+        self
+    }
+
+    pub fn with_pinned_sessions(mut self, pinned_sessions: Vec<String>) -> Self {
+        self.pinned_sessions = pinned_sessions;
+        self
+    }
+
+    pub fn with_session_tags(mut self, session_tags: HashMap<String, Vec<String>>) -> Self {
+        self.session_tags = session_tags;
+        self
+    }
+
+    /// Directories offered by the new-session wizard
+    pub fn with_project_roots(mut self, project_roots: Vec<String>) -> Self {
+        self.project_roots = project_roots;
+        self
+    }
+
+    pub fn with_templates(mut self, templates: Vec<SessionTemplate>) -> Self {
+        self.templates = templates;
+        self
+    }
+
+    /// Pattern for where the worktree wizard puts new worktrees
+    pub fn with_worktree_location(mut self, location: String) -> Self {
+        self.worktree_location = location;
+        self
+    }
+
+    pub fn with_worktree_naming(mut self, naming: String) -> Self {
+        self.worktree_naming = naming;
+        self
+    }
+
     pub async fn show_session_selector(&mut self, context: &SessionContext) -> Result<FzfResult> {
-        let sessions = self.plugin_manager.discover_all_sessions(context).await?;
+        let mut sessions: Vec<SessionItem> = self
+            .plugin_manager
+            .discover_all_sessions(context)
+            .await?
+            .into_iter()
+            .map(|session| {
+                let is_pinned = self.pinned_sessions.contains(&session.name);
+                // Tags from the config add to any the plugin set (e.g. a project's own tags)
+                let mut tags = session.tags.clone();
+                for tag in self.session_tags.get(&session.name).into_iter().flatten() {
+                    if !tags.contains(tag) {
+                        tags.push(tag.clone());
+                    }
+                }
+                session.with_pinned(is_pinned).with_tags(tags)
+            })
+            .collect();
 
-        if sessions.is_empty() {
-            return Ok(FzfResult {
-                key: None,
-                selection: None,
-            });
-        }
+        // Pinned sessions float to the top, keeping the plugin ordering otherwise
+        sessions.sort_by_key(|session| !session.is_pinned);
 
         let formatted_sessions: Vec<String> = sessions
             .iter()
-            .map(|s| s.format_for_display())
+            .map(|s| format!("{}{}{}", s.name, NAME_DELIMITER, s.format_for_display()))
             .collect();
 
+        let title = self.build_title().await?;
+        let preview_script = NamedTempFile::new()?;
+        self.create_preview_script(&preview_script, context).await?;
+
+        let preview_window = if self.preview_enabled {
+            "right:50%:wrap"
+        } else {
+            "hidden"
+        };
+        let mut args = vec![
+            "--border".to_string(),
+            format!("--prompt={}: ", title),
+            "--ansi".to_string(),
+            "--reverse".to_string(),
+            "--multi".to_string(),
+            "--print-query".to_string(),
+            format!("--delimiter={}", NAME_DELIMITER),
+            "--with-nth=2..".to_string(),
+            format!("--expect={}", EXPECTED_KEYS),
+            format!("--preview={} {{1}} {{}}", preview_script.path().display()),
+            format!("--preview-window={}", preview_window),
+            format!(
+                "--bind=ctrl-p:toggle-preview+change-preview({} {{1}} {{}})+change-preview-window(right:50%:wrap)",
+                preview_script.path().display()
+            ),
+            format!(
+                "--bind=?:change-preview({} {{1}} {{}} HELP)+change-preview-window(right:50%:wrap)",
+                preview_script.path().display()
+            ),
+            "--bind=ctrl-d:preview-page-down".to_string(),
+            "--bind=ctrl-u:preview-page-up".to_string(),
+        ];
+        if let Some(header) = &self.header {
+            args.push(format!("--header={}", header));
+        }
+
+        let result = self.run_fzf(&formatted_sessions, &args).await?;
+        self.parse_fzf_result(&result)
+    }
+
+    /// Ask a yes/no question through fzf, defaulting to "No"
+    pub async fn confirm(&self, question: &str, details: &[String]) -> Result<bool> {
+        let choices = vec!["No".to_string(), "Yes".to_string()];
+        let mut args = vec![
+            "--border".to_string(),
+            format!("--prompt={} ", question),
+            "--ansi".to_string(),
+            "--reverse".to_string(),
+            "--no-sort".to_string(),
+        ];
+        if !details.is_empty() {
+            args.push(format!("--header={}", details.join("\n")));
+        }
+
+        let result = self.run_fzf(&choices, &args).await?;
+        Ok(result.trim() == "Yes")
+    }
+
+    /// Run fzf over `items`, in a tmux popup when possible, and return its raw output.
+    /// A cancelled fzf yields an empty string.
+    async fn run_fzf(&self, items: &[String], args: &[String]) -> Result<String> {
         if TmuxClient::is_inside_tmux() && !self.force_no_popup {
-            self.show_tmux_popup(formatted_sessions, context).await
+            self.run_fzf_in_popup(items, args).await
         } else {
-            self.show_regular_fzf(formatted_sessions, context).await
+            self.run_fzf_inline(items, args).await
         }
     }
 
-    async fn show_tmux_popup(&mut self, sessions: Vec<String>, context: &SessionContext) -> Result<FzfResult> {
+    async fn run_fzf_in_popup(&self, items: &[String], args: &[String]) -> Result<String> {
         // Create temporary files for input and output
         let mut input_file = NamedTempFile::new()?;
         let output_file = NamedTempFile::new()?;
-        let preview_script = NamedTempFile::new()?;
+        let status_file = NamedTempFile::new()?;
 
-        // Write sessions to input file
-        for session in &sessions {
-            writeln!(input_file, "{}", session)?;
+        for item in items {
+            writeln!(input_file, "{}", item)?;
         }
         input_file.flush()?;
 
-        // Create preview script
-        self.create_preview_script(&preview_script, context).await?;
-
-        // Prepare fzf command for tmux popup
-        let preview_window = if self.preview_enabled {
-            "right:50%:wrap"
-        } else {
-            "hidden"
-        };
-
-        let title = self.build_title().await?;
-
+        let fzf_args: Vec<String> = args.iter().map(|arg| shell_quote(arg)).collect();
         let tmux_cmd = format!(
-            r#"command cat '{}' | fzf \
-                --prompt='{}: ' \
-                --ansi \
-                --reverse \
-                --border \
-                --expect=ctrl-x,ctrl-r,ctrl-s,ctrl-n,ctrl-p \
-                --preview='{} {{}}' \
-                --preview-window={} \
-                --bind='ctrl-p:toggle-preview+change-preview({} {{}})+change-preview-window(right:50%:wrap)' \
-                --bind='?:change-preview({} {{}} HELP)+change-preview-window(right:50%:wrap)' \
-                --bind='ctrl-d:preview-page-down' \
-                --bind='ctrl-u:preview-page-up' > '{}'"#,
-            input_file.path().display(),
-            title,
-            preview_script.path().display(),
-            preview_window,
-            preview_script.path().display(),
-            preview_script.path().display(),
-            output_file.path().display()
+            "command cat {} | fzf {} > {}; echo $? > {}",
+            shell_quote(&input_file.path().display().to_string()),
+            fzf_args.join(" "),
+            shell_quote(&output_file.path().display().to_string()),
+            shell_quote(&status_file.path().display().to_string())
         );
 
         // Execute tmux popup
         let output = Command::new("tmux")
-            .args(&[
+            .args([
                 "display-popup",
                 "-E",
                 "-w", "60%",
@@ -120,67 +280,37 @@ impl<'a> FzfInterface<'a> {
             .output()
             .await?;
 
-        if !output.status.success() {
-            return Ok(FzfResult {
-                key: None,
-                selection: None,
-            });
+        // fzf records its own exit status; without one the popup never ran it
+        let fzf_status = tokio::fs::read_to_string(status_file.path()).await?;
+        match fzf_status.trim().parse::<i32>() {
+            // fzf exits 1 when nothing matched, yet --print-query output still matters,
+            // and an aborted fzf (130) leaves the file empty
+            Ok(0 | 1 | 130) => Ok(tokio::fs::read_to_string(output_file.path()).await?),
+            Ok(code) => Err(anyhow!("fzf failed in the popup (exit status {})", code)),
+            Err(_) if output.status.success() => Ok(String::new()),
+            Err(_) => Err(anyhow!(
+                "tmux display-popup failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )),
         }
-
-        // Read result
-        let result = tokio::fs::read_to_string(output_file.path()).await?;
-        self.parse_fzf_result(&result)
     }
 
-    async fn show_regular_fzf(&mut self, sessions: Vec<String>, context: &SessionContext) -> Result<FzfResult> {
-        let title = self.build_title().await?;
-        let preview_script = NamedTempFile::new()?;
-        self.create_preview_script(&preview_script, context).await?;
-
-        let preview_window = if self.preview_enabled {
-            "right:50%:wrap"
-        } else {
-            "hidden"
-        };
-        let preview = format!("--preview={} {{}}", preview_script.path().display());
-        let preview_window = format!("--preview-window={}", preview_window);
-        let toggle_preview = format!(
-            "--bind=ctrl-p:toggle-preview+change-preview({} {{}})+change-preview-window(right:50%:wrap)",
-            preview_script.path().display()
-        );
-        let help_preview = format!(
-            "--bind=?:change-preview({} {{}} HELP)+change-preview-window(right:50%:wrap)",
-            preview_script.path().display()
-        );
-
+    async fn run_fzf_inline(&self, items: &[String], args: &[String]) -> Result<String> {
         let mut cmd = Command::new("fzf");
         if !TmuxClient::is_inside_tmux() || !self.force_no_popup {
             cmd.arg("--height=40%");
         }
-        let mut child = cmd.args(&[
-                "--border",
-                &format!("--prompt={}: ", title),
-                "--ansi",
-                "--reverse",
-                "--expect=ctrl-x,ctrl-r,ctrl-s,ctrl-n,ctrl-p",
-                &preview,
-                &preview_window,
-                &toggle_preview,
-                &help_preview,
-                "--bind=ctrl-d:preview-page-down",
-                "--bind=ctrl-u:preview-page-up",
-            ])
+        let mut child = cmd.args(args)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::null())
             .spawn()?;
 
-        // Write sessions to stdin
+        // Write items to stdin
         if let Some(stdin) = child.stdin.take() {
             let mut stdin = tokio::io::BufWriter::new(stdin);
-            for session in &sessions {
-                use tokio::io::AsyncWriteExt;
-                stdin.write_all(session.as_bytes()).await?;
+            for item in items {
+                stdin.write_all(item.as_bytes()).await?;
                 stdin.write_all(b"\n").await?;
             }
             stdin.flush().await?;
@@ -188,15 +318,12 @@ impl<'a> FzfInterface<'a> {
 
         let output = child.wait_with_output().await?;
 
-        if !output.status.success() {
-            return Ok(FzfResult {
-                key: None,
-                selection: None,
-            });
+        // fzf exits 1 when nothing matched; with --print-query the query is still printed
+        if !output.status.success() && output.status.code() != Some(1) {
+            return Ok(String::new());
         }
 
-        let result = String::from_utf8_lossy(&output.stdout);
-        self.parse_fzf_result(&result)
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
     }
 
     async fn create_preview_script(&self, script_file: &NamedTempFile, _context: &SessionContext) -> Result<()> {
@@ -204,11 +331,10 @@ impl<'a> FzfInterface<'a> {
         let current_exe = std::env::current_exe()?.display().to_string();
 
         let script_content = format!(r#"#!/bin/bash
-selection="$1"
-mode="$2"
-
-# Extract session name from formatted display
-session_name=$(echo "$selection" | sed -E 's/^[^●→○󱗽★]*[●→○󱗽★][^[:space:]]* ([^[:space:]]+).*/\1/')
+# fzf passes the hidden name field, then the whole line
+session_name="$1"
+selection="$2"
+mode="$3"
 
 case "$mode" in
     "HELP")
@@ -242,7 +368,8 @@ esac
 
     async fn check_missing_dependencies(&self) -> Vec<String> {
         let mut missing = Vec::new();
-        let common_deps = ["tmuxinator", "git"];
+        // tmuxinator is left out: projects start without the gem unless they need it
+        let common_deps = ["git"];
 
         for dep in &common_deps {
             if !self.command_exists(dep).await {
@@ -263,189 +390,615 @@ esac
     }
 
     fn parse_fzf_result(&self, result: &str) -> Result<FzfResult> {
-        let lines: Vec<&str> = result.trim().lines().collect();
+        // --print-query puts the query first, then --expect always adds the key line
+        // (empty when Enter was pressed); an aborted fzf prints nothing at all
+        let mut lines = result.lines();
+        let query = lines.next().unwrap_or("").to_string();
+        let key = lines
+            .next()
+            .filter(|key| !key.is_empty())
+            .map(|key| key.to_string());
+        let selections = lines
+            .filter(|line| !line.is_empty())
+            .map(|line| line.to_string())
+            .collect();
+
+        Ok(FzfResult { query, key, selections })
+    }
 
-        if lines.is_empty() {
-            return Ok(FzfResult {
-                key: None,
-                selection: None,
-            });
+    /// The session a picker row stands for, from its hidden name field
+    pub fn extract_session_name(&self, formatted_selection: &str) -> String {
+        formatted_selection
+            .split(NAME_DELIMITER)
+            .next()
+            .unwrap_or(formatted_selection)
+            .to_string()
+    }
+
+    pub async fn switch_to_session(&self, session_name: &str, context: &SessionContext) -> Result<()> {
+        self.plugin_manager.switch_to_session(session_name, context).await
+    }
+
+    /// Enter on a query that matched nothing: open it as a directory, as a project a plugin
+    /// knows by name (e.g. a tmuxinator project), or else as a new session with that name
+    pub async fn open_query(&self, query: &str, context: &SessionContext) -> Result<()> {
+        let dir = directories::expand_home(query);
+        if query.contains('/') && dir.is_dir() {
+            return self.open_directory(&dir, context).await;
         }
 
-        if lines.len() == 1 {
-            // Only selection, no key pressed
-            Ok(FzfResult {
-                key: None,
-                selection: Some(lines[0].to_string()),
-            })
-        } else if lines.len() >= 2 {
-            // Key and selection
-            Ok(FzfResult {
-                key: Some(lines[0].to_string()),
-                selection: Some(lines[1].to_string()),
-            })
-        } else {
-            Ok(FzfResult {
-                key: None,
-                selection: None,
-            })
+        if let Some(plugin) = self.plugin_manager.find_specific_plugin(query, context).await {
+            let metadata = plugin.resolve(query, context).await?;
+            return plugin.switch(query, &metadata).await;
         }
+
+        let tmux = TmuxClient::new();
+        let session_name = naming::sanitize(query);
+        if !tmux.has_session(&session_name).await {
+            let path = std::env::current_dir()?.to_string_lossy().to_string();
+            tmux.new_session(&session_name, Some(&path)).await?;
+            tmux.stamp_session(&session_name, &SessionStamp::new("active").with_path(&path)).await?;
+        }
+        tmux.switch_or_attach(&session_name).await
     }
 
-    pub fn extract_session_name(&self, formatted_selection: &str) -> String {
-        // Extract session name from formatted display string
-        // Simple approach: split by whitespace and take the second token (first is icon)
-        let tokens: Vec<&str> = formatted_selection.split_whitespace().collect();
-
-        if tokens.len() >= 2 {
-            // Second token is the session name, might have suffix in parentheses
-            let name_part = tokens[1];
-            // Remove any plugin suffix in parentheses
-            if let Some(open_paren) = name_part.find('(') {
-                name_part[..open_paren].trim().to_string()
-            } else {
-                name_part.to_string()
+    /// Switch to the session for `dir`'s project, creating it at the repo root if needed
+    pub async fn open_directory(&self, dir: &Path, context: &SessionContext) -> Result<()> {
+        let tmux = TmuxClient::new();
+        let session_name = directories::ensure_session_for_path(&tmux, dir, context).await?;
+        tmux.switch_or_attach(&session_name).await
+    }
+
+    /// Kill sessions, confirming once when any of them runs something besides a shell.
+    /// Running sessions are snapshotted into the trash bin first so the kill can be undone.
+    pub async fn kill_sessions(&self, session_names: &[String], context: &SessionContext) -> Result<BulkReport> {
+        let tmux = TmuxClient::new();
+        let mut report = BulkReport::default();
+
+        // Protected sessions are reported as failures instead of being asked about
+        let (protected, session_names): (Vec<String>, Vec<String>) = session_names
+            .iter()
+            .cloned()
+            .partition(|session_name| self.plugin_manager.is_protected(session_name, context));
+        for session_name in protected {
+            report.failed.push((session_name, "protected (Alt-l to unprotect)".to_string()));
+        }
+        if session_names.is_empty() {
+            return Ok(report);
+        }
+
+        let mut busy = Vec::new();
+        for session_name in &session_names {
+            if let Ok(panes) = tmux.list_panes(session_name).await {
+                busy.extend(
+                    panes
+                        .iter()
+                        .filter(|pane| pane.is_busy())
+                        .map(|pane| format!("{} {}:{} running {}", session_name, pane.window_index, pane.window_name, pane.current_command)),
+                );
             }
-        } else {
-            // Fallback: return the whole string
-            formatted_selection.to_string()
         }
-    }
 
-    pub async fn switch_to_session(&self, session_name: &str, context: &SessionContext) -> Result<()> {
-        self.plugin_manager.switch_to_session(session_name, context).await
+        let question = match session_names.as_slice() {
+            [session_name] => format!("Kill '{}'?", session_name),
+            _ => format!("Kill {} sessions?", session_names.len()),
+        };
+        if !busy.is_empty() && !self.confirm(&question, &busy).await? {
+            report.skipped.extend(session_names);
+            return Ok(report);
+        }
+
+        let killed = self.kill_selected(&session_names, context, false).await?;
+        report.succeeded.extend(killed.succeeded);
+        report.skipped.extend(killed.skipped);
+        report.failed.extend(killed.failed);
+        Ok(report)
     }
 
-    pub async fn kill_session(&self, session_name: &str, context: &SessionContext) -> Result<()> {
-        self.plugin_manager.kill_session(session_name, context).await
+    /// Kill a worktree session and remove its worktree, asking first when the worktree holds
+    /// uncommitted changes, unpushed commits or stashes. Offers to delete the branch once it
+    /// is merged. Returns a status line; `None` when cancelled.
+    pub async fn remove_worktree(&self, session_name: &str, context: &SessionContext) -> Result<Option<String>> {
+        let plugin = match self.plugin_manager.find_plugin_for_session(session_name, context).await {
+            Some(plugin) if plugin.name() == "worktree" => plugin,
+            _ => return Err(anyhow!("'{}' is not a worktree", session_name)),
+        };
+        let metadata = plugin.resolve(session_name, context).await?;
+        let dir = PathBuf::from(metadata.path.ok_or_else(|| anyhow!("No worktree path for '{}'", session_name))?);
+        let path = git::repo_root(&dir).await.unwrap_or(dir);
+
+        // The entry is the running session's tmux name, or the identity of a worktree without one
+        let session = context
+            .all_tmux_sessions
+            .iter()
+            .find(|session| session.name == session_name)
+            .or_else(|| naming::find_session(session_name, &context.all_tmux_sessions));
+        if let Some(session) = session.filter(|session| self.plugin_manager.is_protected(&session.name, context)) {
+            return Err(anyhow!("Session '{}' is protected; unprotect it before removing its worktree", session.name));
+        }
+
+        // Only linked worktrees have a .git file; the main checkout holds the repository itself
+        if !git::is_worktree_dir(&path).await {
+            return Err(anyhow!("Refusing to remove the main worktree {}", directories::abbreviate_home(&path)));
+        }
+
+        let worktrees = git::list_worktrees(&path).await?;
+        let main = worktrees.first().ok_or_else(|| anyhow!("No worktrees found in {}", path.display()))?;
+        let branch = worktrees
+            .iter()
+            .find(|worktree| worktree.path == path)
+            .and_then(|worktree| worktree.branch.clone());
+
+        let status = git::worktree_status(&path).await?;
+        if !status.is_clean() {
+            let mut details = Vec::new();
+            for (label, lines) in [
+                ("uncommitted change(s), discarded on removal", &status.uncommitted),
+                ("unpushed commit(s)", &status.unpushed),
+                ("stash(es)", &status.stashes),
+            ] {
+                if !lines.is_empty() {
+                    details.push(format!("{} {}:", lines.len(), label));
+                    details.extend(lines.iter().take(5).map(|line| format!("  {}", line)));
+                }
+            }
+
+            let question = format!("Remove worktree '{}' anyway?", session_name);
+            if !self.confirm(&question, &details).await? {
+                return Ok(None);
+            }
+        }
+
+        // A failed removal leaves the session running; only then is it killed
+        git::remove_worktree(&main.path, &path, !status.uncommitted.is_empty()).await?;
+        let mut message = format!("Removed worktree {}", directories::abbreviate_home(&path));
+
+        // Killed without a trash snapshot: its panes' directory is gone, so `undo-kill` could not restore them
+        if let Some(session) = session {
+            plugin.kill(&session.name).await?;
+        }
+
+        // Only offer to delete a branch that lives on in the main checkout's branch
+        if let (Some(branch), Some(main_branch)) = (branch, &main.branch) {
+            if git::is_merged(&main.path, &branch, main_branch).await {
+                let question = format!("Delete branch '{}' (merged into {})?", branch, main_branch);
+                if self.confirm(&question, &[]).await? {
+                    git::delete_branch(&main.path, &branch).await?;
+                    message.push_str(&format!(" and branch {}", branch));
+                }
+            }
+        }
+
+        Ok(Some(message))
     }
 
-    pub async fn start_session(&self, session_name: &str, context: &SessionContext) -> Result<()> {
-        self.plugin_manager.start_session(session_name, context).await
+    /// Add the windows a running tmuxinator session lacks compared to its project file
+    pub async fn reconcile_project(&self, session_name: &str, context: &SessionContext) -> Result<Option<String>> {
+        let plugin = self.plugin_manager.plugin("tmuxinator")
+            .ok_or_else(|| anyhow!("The tmuxinator plugin is not available"))?;
+        let metadata = plugin.resolve(session_name, context).await
+            .map_err(|_| anyhow!("'{}' is not a tmuxinator project", session_name))?;
+
+        let missing = plugin.missing_windows(session_name, &metadata).await?;
+        if missing.is_empty() {
+            return Ok(Some(format!("{} has every window of its project", session_name)));
+        }
+
+        let question = format!("Add {} missing window(s) to '{}'?", missing.len(), session_name);
+        if !self.confirm(&question, &missing).await? {
+            return Ok(None);
+        }
+
+        let added = plugin.add_missing_windows(session_name, &metadata).await?;
+        Ok(Some(format!("Added {} to {}", added.join(", "), session_name)))
     }
 
-    pub async fn create_new_session(&self, _context: &SessionContext) -> Result<()> {
-        // Prompt for new session name using fzf
-        let prompt_result = self.prompt_for_session_name().await?;
-        if let Some(session_name) = prompt_result {
-            // Create the new session
-            let tmux = TmuxClient::new();
-            tmux.new_session(&session_name, None).await?;
+    /// Show prune candidates as a table with everything preselected and kill what stays selected
+    pub async fn prune_sessions(&self, candidates: &[PruneCandidate], context: &SessionContext) -> Result<BulkReport> {
+        if candidates.is_empty() {
+            return Ok(BulkReport::default());
         }
-        Ok(())
+
+        let (header, rows) = format_table(candidates);
+        let rows: Vec<String> = candidates
+            .iter()
+            .zip(rows)
+            .map(|(candidate, row)| format!("{}{}{}", candidate.session_name, NAME_DELIMITER, row))
+            .collect();
+        let args = vec![
+            "--border".to_string(),
+            "--prompt=Prune (tab to keep): ".to_string(),
+            "--reverse".to_string(),
+            "--multi".to_string(),
+            "--no-sort".to_string(),
+            format!("--delimiter={}", NAME_DELIMITER),
+            "--with-nth=2..".to_string(),
+            "--bind=load:select-all".to_string(),
+            format!("--header={}", header),
+        ];
+
+        let result = self.run_fzf(&rows, &args).await?;
+        let selected: Vec<String> = result
+            .lines()
+            .filter(|row| !row.is_empty())
+            .map(|row| self.extract_session_name(row))
+            .collect();
+
+        self.kill_selected(&selected, context, false).await
     }
 
-    async fn prompt_for_session_name(&self) -> Result<Option<String>> {
-        if TmuxClient::is_inside_tmux() {
-            // Use tmux command-prompt for input
-            self.tmux_prompt("New session name:", "new-session -d -s '%%'").await
-        } else {
-            // Use read command for non-tmux environments
-            self.shell_prompt("Enter new session name").await
+    /// Kill sessions the user already picked explicitly, without asking again.
+    /// Protected sessions fail unless `force` is set.
+    pub async fn kill_selected(&self, session_names: &[String], context: &SessionContext, force: bool) -> Result<BulkReport> {
+        let mut report = BulkReport::default();
+
+        for session_name in session_names {
+            report.record(session_name, self.kill_session(session_name, context, force).await);
         }
+
+        Ok(report)
+    }
+
+    async fn kill_session(&self, session_name: &str, context: &SessionContext, force: bool) -> Result<bool> {
+        self.plugin_manager.kill_session(session_name, context, force).await?;
+        Ok(true)
     }
 
-    pub async fn rename_session(&self, old_session_name: &str, _context: &SessionContext) -> Result<()> {
-        // Prompt for new session name using fzf
-        let prompt_result = self.prompt_for_rename(old_session_name).await?;
-        if let Some(new_session_name) = prompt_result {
-            // Rename the session
-            let tmux = TmuxClient::new();
-            tmux.rename_session(old_session_name, &new_session_name).await?;
+    /// Protect every selected session unless all of them already are, in which case unprotect them
+    pub async fn toggle_protection(&self, session_names: &[String], context: &SessionContext) -> Result<BulkReport> {
+        let tmux = TmuxClient::new();
+        let mut report = BulkReport::default();
+
+        let all_protected = session_names
+            .iter()
+            .all(|session_name| self.plugin_manager.is_protected(session_name, context));
+
+        for session_name in session_names {
+            if !tmux.has_session(session_name).await {
+                report.skipped.push(session_name.clone());
+                continue;
+            }
+
+            let result = if !all_protected {
+                tmux.set_session_option(session_name, PROTECTED_OPTION, "1").await
+            } else if self.plugin_manager.is_protected_by_pattern(session_name) {
+                Err(anyhow!("protected by a config pattern"))
+            } else {
+                tmux.unset_session_option(session_name, PROTECTED_OPTION).await
+            };
+            report.record(session_name, result.map(|_| true));
         }
-        Ok(())
+
+        Ok(report)
     }
 
-    async fn prompt_for_rename(&self, current_name: &str) -> Result<Option<String>> {
-        if TmuxClient::is_inside_tmux() {
-            // Use tmux command-prompt for input with current name as default
-            self.tmux_prompt_with_default(&format!("Rename '{}' to:", current_name), &format!("rename-session -t '{}' '{{}}'", current_name), current_name).await
-        } else {
-            // Use read command for non-tmux environments
-            self.shell_prompt(&format!("Rename '{}' to", current_name)).await
+    /// Recreate the most recently killed session from the trash bin
+    pub async fn undo_kill(&self) -> Result<Vec<String>> {
+        let report = TrashBin::new().restore_latest(&TmuxClient::new(), 1).await?;
+        if let Some((_, error)) = report.failed.into_iter().next() {
+            return Err(anyhow!(error));
         }
+        Ok(report.restored)
     }
 
-    async fn tmux_prompt(&self, prompt: &str, _command_template: &str) -> Result<Option<String>> {
-        // Use tmux display-popup with a simple input method
-        self.tmux_popup_prompt(prompt, None).await
+    /// Start sessions in the background, leaving running ones alone
+    pub async fn start_sessions(&self, session_names: &[String], context: &SessionContext) -> Result<BulkReport> {
+        let tmux = TmuxClient::new();
+        let mut report = BulkReport::default();
+
+        for session_name in session_names {
+            if tmux.has_session(session_name).await {
+                report.skipped.push(session_name.clone());
+                continue;
+            }
+
+            let result = self.plugin_manager.start_session(session_name, context).await;
+            report.record(session_name, result.map(|_| true));
+        }
+
+        Ok(report)
     }
 
-    async fn tmux_prompt_with_default(&self, prompt: &str, _command_template: &str, default: &str) -> Result<Option<String>> {
-        // Use tmux display-popup with default value
-        self.tmux_popup_prompt(prompt, Some(default)).await
+    pub async fn prompt_for_tag(&self) -> Result<Option<String>> {
+        self.prompt_for_input("Tag (toggles on selection)", None).await
     }
 
-    async fn tmux_popup_prompt(&self, prompt: &str, _default: Option<&str>) -> Result<Option<String>> {
-        use std::process::Stdio;
-        use tokio::process::Command;
-        use tempfile::NamedTempFile;
+    /// New-session wizard: pick a directory, confirm a name derived from it, then optionally a template
+    pub async fn create_new_session(&self, context: &SessionContext) -> Result<Option<String>> {
+        let Some(dir) = self.prompt_for_directory().await? else {
+            return Ok(None);
+        };
 
-        // Create a temporary file to store the result
-        let result_file = NamedTempFile::new()?;
-        let result_path = result_file.path().to_string_lossy();
+        let derived_name = directories::derive_session_name(&dir).await;
+        let default_name = naming::unique_name(&derived_name, |candidate| {
+            context.all_tmux_sessions.iter().any(|session| session.name == candidate)
+        });
+        let prompt = format!("Session name for {}", directories::abbreviate_home(&dir));
+        let Some(session_name) = self.prompt_for_valid_name(&prompt, &default_name, None, context).await? else {
+            return Ok(None);
+        };
 
-        // Use tmux command-prompt instead of popup for better input handling
-        let tmux_command = format!(
-            "run-shell 'echo \"%%\" > \"{}\"'",
-            result_path
-        );
+        let template = self.prompt_for_template().await?;
 
-        let mut tmux_cmd = Command::new("tmux");
-        tmux_cmd
-            .arg("command-prompt")
-            .arg("-p")
-            .arg(&format!("{}: ", prompt))
-            .arg(&tmux_command)
-            .stdout(Stdio::null())
-            .stderr(Stdio::null());
-
-        let output = tmux_cmd.output().await?;
-
-        // Small delay to allow file write to complete
-        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
-
-        // Read the result from the temporary file
-        if output.status.success() {
-            if let Ok(result) = std::fs::read_to_string(result_path.as_ref()) {
-                let input = result.trim();
-                if !input.is_empty() {
-                    return Ok(Some(input.to_string()));
-                }
+        // Plain sessions are owned by the active plugin
+        let tmux = TmuxClient::new();
+        let path = dir.to_string_lossy();
+        tmux.new_session(&session_name, Some(&path)).await?;
+        tmux.stamp_session(&session_name, &SessionStamp::new("active").with_path(&path)).await?;
+
+        if let Some(template) = template {
+            template.apply(&tmux, &session_name, &path).await?;
+        }
+
+        Ok(Some(session_name))
+    }
+
+    /// Worktree wizard for the repository of `session_name` (or the current directory): pick a
+    /// branch without a worktree, or start a new one, then add the worktree and switch to its
+    /// session. Returns the session's name; `None` when cancelled.
+    pub async fn create_worktree(&self, session_name: Option<&str>, context: &SessionContext) -> Result<Option<String>> {
+        let dir = session_name
+            .and_then(|name| context.all_tmux_sessions.iter().find(|session| session.name == name))
+            .and_then(|session| session.options.path.clone().or_else(|| session.current_path.clone()))
+            .map(PathBuf::from)
+            .map_or_else(std::env::current_dir, Ok)?;
+
+        let worktrees = git::list_worktrees(&dir)
+            .await
+            .map_err(|_| anyhow!("Not a git repository: {}", directories::abbreviate_home(&dir)))?;
+        let main = worktrees.first().ok_or_else(|| anyhow!("No worktrees found in {}", dir.display()))?;
+        let branches = git::list_branches(&main.path).await?;
+
+        // Branches already checked out can't get a second worktree, and a remote branch
+        // is only offered while it has no local counterpart
+        let checked_out: Vec<&str> = worktrees.iter().filter_map(|worktree| worktree.branch.as_deref()).collect();
+        let local: Vec<&str> = branches.iter().filter(|branch| !branch.is_remote).map(|branch| branch.name.as_str()).collect();
+        let available: Vec<&git::Branch> = branches
+            .iter()
+            .filter(|branch| !checked_out.contains(&branch.local_name()))
+            .filter(|branch| !branch.is_remote || !local.contains(&branch.local_name()))
+            .collect();
+
+        let mut items = vec![format!("{}HEAD", NEW_BRANCH_FROM)];
+        if let Some(default_branch) = ["main", "master"].into_iter().find(|name| local.contains(name)) {
+            items.push(format!("{}{}", NEW_BRANCH_FROM, default_branch));
+        }
+        items.extend(available.iter().map(|branch| branch.name.clone()));
+
+        let args = vec![
+            "--border".to_string(),
+            "--reverse".to_string(),
+            "--no-sort".to_string(),
+            format!("--prompt=Worktree ({})> ", main.repo_name()),
+            "--header=Pick a branch to check out into a new worktree".to_string(),
+        ];
+        let output = self.run_fzf(&items, &args).await?;
+        let choice = output.trim();
+        if choice.is_empty() {
+            return Ok(None);
+        }
+
+        let (branch, base) = if let Some(base) = choice.strip_prefix(NEW_BRANCH_FROM) {
+            let prompt = format!("New branch from {}", base);
+            let Some(branch) = self.prompt_for_input(&prompt, None).await? else {
+                return Ok(None);
+            };
+            // git runs in the main checkout, so HEAD has to be read where the session is
+            let base = if base == "HEAD" { git::head_commit(&dir).await? } else { base.to_string() };
+            (branch, Some(base))
+        } else {
+            match available.iter().find(|branch| branch.name == choice) {
+                // A remote branch gets a local branch of the same name that tracks it
+                Some(branch) if branch.is_remote => (branch.local_name().to_string(), Some(branch.name.clone())),
+                Some(branch) => (branch.name.clone(), None),
+                None => return Err(anyhow!("Unknown branch: {}", choice)),
             }
+        };
+
+        let path = git::worktree_location(&self.worktree_location, &main.path, &main.repo_name(), &branch);
+        if path.exists() {
+            return Err(anyhow!("{} already exists", directories::abbreviate_home(&path)));
         }
+        git::add_worktree(&main.path, &path, &branch, base.as_deref()).await?;
+
+        // The worktree plugin owns the session from here on, under the name discovery gives it
+        let plugin = self
+            .plugin_manager
+            .plugin("worktree")
+            .ok_or_else(|| anyhow!("Worktree plugin not loaded"))?;
+        let path = path.canonicalize()?;
+        let worktree_name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        let identity = naming::checkout_identity(&self.worktree_naming, &main.repo_name(), &branch, &worktree_name);
+        let metadata = SessionMetadata::new("worktree".to_string())
+            .with_exists(false)
+            .with_path(path.to_string_lossy().to_string());
+
+        plugin.switch(&identity, &metadata).await?;
+        Ok(Some(identity))
+    }
+
+    /// Pick a candidate directory, or type any path; `None` when cancelled
+    async fn prompt_for_directory(&self) -> Result<Option<PathBuf>> {
+        let items: Vec<String> = directories::candidate_directories(&self.project_roots)
+            .await
+            .iter()
+            .map(|dir| directories::abbreviate_home(dir))
+            .collect();
+
+        let args = vec![
+            "--border".to_string(),
+            "--reverse".to_string(),
+            "--prompt=Directory> ".to_string(),
+            "--header=Pick a directory for the new session, or type a path".to_string(),
+            "--print-query".to_string(),
+        ];
+        let output = self.run_fzf(&items, &args).await?;
+
+        // --print-query puts the query first; the selection follows unless nothing matched
+        let mut lines = output.lines();
+        let query = lines.next().unwrap_or("").trim();
+        let choice = match lines.next() {
+            Some(selection) => selection,
+            None if !query.is_empty() => query,
+            None => return Ok(None),
+        };
 
-        Ok(None)
+        let dir = directories::expand_home(choice);
+        if !dir.is_dir() {
+            return Err(anyhow!("Not a directory: {}", choice));
+        }
+        Ok(Some(dir))
     }
 
-    async fn shell_prompt(&self, prompt: &str) -> Result<Option<String>> {
-        use std::io::Write;
-        use std::process::{Command, Stdio};
-        use std::fs::OpenOptions;
+    /// Pick one of the configured templates; skipped entirely when none are configured
+    async fn prompt_for_template(&self) -> Result<Option<&SessionTemplate>> {
+        if self.templates.is_empty() {
+            return Ok(None);
+        }
 
-        // Try to open the controlling terminal directly
-        if let Ok(mut tty) = OpenOptions::new().read(true).write(true).open("/dev/tty") {
-            // Write prompt to tty
-            writeln!(tty, "{}: ", prompt).ok();
-            tty.flush().ok();
+        let items: Vec<String> = std::iter::once(NO_TEMPLATE.to_string())
+            .chain(self.templates.iter().map(|template| template.name.clone()))
+            .collect();
+        let args = vec![
+            "--border".to_string(),
+            "--reverse".to_string(),
+            "--prompt=Template> ".to_string(),
+        ];
+        let output = self.run_fzf(&items, &args).await?;
+        let choice = output.trim();
+
+        Ok(self.templates.iter().find(|template| template.name == choice))
+    }
 
-            // Use /dev/tty for both input and output to bypass stdio redirection
-            let mut cmd = Command::new("bash");
-            cmd.arg("-c")
-               .arg("read input < /dev/tty && echo $input")
-               .stdout(Stdio::piped());
+    /// Prompt for a new name and hand the rename to the owning plugin; `None` means cancelled
+    pub async fn rename_session(&self, old_session_name: &str, context: &SessionContext) -> Result<Option<Renamed>> {
+        let prompt = format!("Rename '{}' to", old_session_name);
+        let Some(new_name) = self
+            .prompt_for_valid_name(&prompt, old_session_name, Some(old_session_name), context)
+            .await?
+        else {
+            return Ok(None);
+        };
 
-            if let Ok(output) = cmd.output() {
-                if output.status.success() {
-                    let result = String::from_utf8_lossy(&output.stdout);
-                    let input = result.trim();
-                    if !input.is_empty() {
-                        return Ok(Some(input.to_string()));
-                    }
+        let warning = self.plugin_manager.rename_session(old_session_name, &new_name, context).await?;
+        Ok(Some(Renamed { new_name, warning }))
+    }
+
+    /// Keep prompting until the name is valid and unused, showing the last error in the prompt
+    async fn prompt_for_valid_name(
+        &self,
+        prompt: &str,
+        default: &str,
+        current_name: Option<&str>,
+        context: &SessionContext,
+    ) -> Result<Option<String>> {
+        let mut value = default.to_string();
+        let mut error: Option<String> = None;
+
+        loop {
+            let label = match &error {
+                Some(error) => format!("{} - {}", error, prompt),
+                None => prompt.to_string(),
+            };
+
+            let Some(input) = self.prompt_for_input(&label, Some(&value)).await? else {
+                return Ok(None);
+            };
+            if current_name == Some(input.as_str()) {
+                return Ok(None); // Unchanged
+            }
+
+            match self.plugin_manager.validate_new_name(&input, context) {
+                Ok(()) => return Ok(Some(input)),
+                Err(e) => {
+                    error = Some(e.to_string());
+                    value = input;
                 }
             }
         }
+    }
+
+    /// Read a line of input pre-filled with `default`; `None` when cancelled or left empty.
+    /// fzf's query line does the editing, so this works wherever the picker does.
+    async fn prompt_for_input(&self, prompt: &str, default: Option<&str>) -> Result<Option<String>> {
+        let args = vec![
+            "--border".to_string(),
+            "--reverse".to_string(),
+            format!("--prompt={}: ", prompt),
+            format!("--query={}", default.unwrap_or("")),
+            "--print-query".to_string(),
+        ];
+
+        // Enter on an empty list still prints the query, while a cancelled fzf prints nothing
+        let output = self.run_fzf(&[], &args).await?;
+        Ok(output
+            .lines()
+            .next()
+            .map(|input| input.trim().to_string())
+            .filter(|input| !input.is_empty()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_enter_with_multiple_selections() {
+        let manager = PluginManager::new();
+        let result = FzfInterface::new(&manager)
+            .parse_fzf_result("al\n\n● alpha\n● beta\n")
+            .unwrap();
+
+        assert_eq!(result.query, "al");
+        assert_eq!(result.key, None);
+        assert_eq!(result.selections, vec!["● alpha", "● beta"]);
+    }
+
+    #[test]
+    fn parses_expected_key_with_selection() {
+        let manager = PluginManager::new();
+        let result = FzfInterface::new(&manager)
+            .parse_fzf_result("\nctrl-x\n● alpha\n")
+            .unwrap();
+
+        assert_eq!(result.key.as_deref(), Some("ctrl-x"));
+        assert_eq!(result.selections, vec!["● alpha"]);
+    }
+
+    #[test]
+    fn parses_cancelled_selector() {
+        let manager = PluginManager::new();
+        let result = FzfInterface::new(&manager).parse_fzf_result("").unwrap();
+
+        assert_eq!(result.key, None);
+        assert!(result.selections.is_empty());
+    }
+
+    #[test]
+    fn parses_unmatched_query() {
+        let manager = PluginManager::new();
+        let result = FzfInterface::new(&manager).parse_fzf_result("~/src/new-app\n\n").unwrap();
+
+        assert_eq!(result.query, "~/src/new-app");
+        assert_eq!(result.key, None);
+        assert!(result.selections.is_empty());
+    }
+
+    #[test]
+    fn extracts_name_from_hidden_field() {
+        let manager = PluginManager::new();
+        let ui = FzfInterface::new(&manager);
+
+        assert_eq!(ui.extract_session_name("my api\t● my api \u{f435} #work (worktree)"), "my api");
+    }
+
+    #[test]
+    fn summarizes_bulk_failures() {
+        let mut report = BulkReport::default();
+        report.record("alpha", Ok(true));
+        report.record("beta", Err(anyhow!("no such session")));
 
-        Ok(None)
+        assert_eq!(report.summary("Killed"), "Killed 1 session(s)\nFailed beta: no such session");
     }
 }
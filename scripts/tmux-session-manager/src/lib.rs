@@ -3,7 +3,7 @@ pub mod plugins;
 pub mod config;
 
 // Re-export commonly used items for testing
-pub use core::session::{SessionContext, SessionItem, SessionMetadata, TmuxSession};
+pub use core::session::{SessionContext, SessionItem, SessionMetadata, SessionOptions, SessionStamp, TmuxSession};
 pub use core::tmux::TmuxClient;
 pub use plugins::{PluginManager, SessionPlugin};
 pub use config::Config;
\ No newline at end of file
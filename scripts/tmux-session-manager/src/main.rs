@@ -1,16 +1,16 @@
 use anyhow::Result;
 use clap::{Arg, Command};
 
-mod config;
-mod core;
-mod plugins;
-
-use config::Config;
-use core::{
+use tmux_session_manager::config::Config;
+use tmux_session_manager::core::{
+    directories,
+    prune::{format_table, PruneRules},
+    session::SessionContext,
     tmux::TmuxClient,
+    trash::TrashBin,
     ui::FzfInterface,
 };
-use plugins::PluginManager;
+use tmux_session_manager::plugins::{worktree::WorktreePlugin, PluginManager};
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -49,13 +49,93 @@ async fn main() -> Result<()> {
                 .help("Show help preview")
                 .action(clap::ArgAction::SetTrue),
         )
+        .subcommand(
+            Command::new("undo-kill")
+                .about("Recreate the most recently killed sessions from the trash bin")
+                .arg(
+                    Arg::new("count")
+                        .help("Number of sessions to restore")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("1"),
+                ),
+        )
+        .subcommand(
+            Command::new("kill")
+                .about("Kill sessions, keeping a snapshot in the trash bin")
+                .arg(
+                    Arg::new("sessions")
+                        .help("Sessions to kill")
+                        .required(true)
+                        .num_args(1..),
+                )
+                .arg(
+                    Arg::new("force")
+                        .long("force")
+                        .short('f')
+                        .help("Kill protected sessions too")
+                        .action(clap::ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("open")
+                .about("Switch to the session for a directory, creating it if needed")
+                .arg(
+                    Arg::new("path")
+                        .help("Directory to open (defaults to the current directory)"),
+                ),
+        )
+        .subcommand(
+            Command::new("prune")
+                .about("Kill idle, empty or orphaned sessions")
+                .arg(
+                    Arg::new("idle-hours")
+                        .long("idle-hours")
+                        .help("Idle threshold in hours (defaults to the configured value)")
+                        .value_parser(clap::value_parser!(i64)),
+                )
+                .arg(
+                    Arg::new("dry-run")
+                        .long("dry-run")
+                        .help("Only list the sessions that would be pruned")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("yes")
+                        .long("yes")
+                        .short('y')
+                        .help("Kill every candidate without asking")
+                        .action(clap::ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("clean-worktrees")
+                .about("Prune deleted git worktrees and kill the sessions left in them")
+                .arg(
+                    Arg::new("dry-run")
+                        .long("dry-run")
+                        .help("Only list what would be cleaned up")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("yes")
+                        .long("yes")
+                        .short('y')
+                        .help("Clean up without asking")
+                        .action(clap::ArgAction::SetTrue),
+                ),
+        )
         .get_matches();
 
     // Load configuration
     let mut config = Config::load().await?;
 
     // Initialize components
-    let mut plugin_manager = PluginManager::new();
+    let mut plugin_manager = PluginManager::new()
+        .with_protected_patterns(config.protected_sessions.clone())
+        .with_projects(config.projects.clone(), config.templates.clone())
+        .with_repo_scan(&config.repo_scan_settings())
+        .with_worktree_settings(&config.worktrees)
+        .with_tmuxinator_project_dirs(&config.known_project_dirs());
 
     // Load dynamic plugins
     let plugin_dir = config.get_plugin_dir();
@@ -67,7 +147,7 @@ async fn main() -> Result<()> {
 
     // Handle command line arguments
     if matches.get_flag("doctor") {
-        return doctor_command(&plugin_manager).await;
+        return doctor_command(&plugin_manager, &config, &tmux).await;
     }
 
     if matches.get_flag("info") {
@@ -85,6 +165,52 @@ async fn main() -> Result<()> {
         return help_preview_command().await;
     }
 
+    if let Some(undo_matches) = matches.subcommand_matches("undo-kill") {
+        let count = undo_matches.get_one::<usize>("count").copied().unwrap_or(1);
+        return undo_kill_command(&tmux, count).await;
+    }
+
+    if let Some(kill_matches) = matches.subcommand_matches("kill") {
+        let session_names: Vec<String> = kill_matches
+            .get_many::<String>("sessions")
+            .map(|values| values.cloned().collect())
+            .unwrap_or_default();
+        return kill_command(&plugin_manager, &tmux, &session_names, kill_matches.get_flag("force")).await;
+    }
+
+    if let Some(open_matches) = matches.subcommand_matches("open") {
+        let path = open_matches.get_one::<String>("path").map(String::as_str);
+        return open_command(&plugin_manager, &tmux, path).await;
+    }
+
+    if let Some(prune_matches) = matches.subcommand_matches("prune") {
+        let mut rules = PruneRules::from_config(&config);
+        if let Some(hours) = prune_matches.get_one::<i64>("idle-hours") {
+            rules = rules.with_idle_threshold(chrono::Duration::hours(*hours));
+        }
+        return prune_command(
+            &plugin_manager,
+            &tmux,
+            &rules,
+            prune_matches.get_flag("dry-run"),
+            prune_matches.get_flag("yes"),
+            matches.get_flag("no-popup"),
+        )
+        .await;
+    }
+
+    if let Some(clean_matches) = matches.subcommand_matches("clean-worktrees") {
+        return clean_worktrees_command(
+            &plugin_manager,
+            &config,
+            &tmux,
+            clean_matches.get_flag("dry-run"),
+            clean_matches.get_flag("yes"),
+            matches.get_flag("no-popup"),
+        )
+        .await;
+    }
+
     // Main interactive mode
     run_interactive_mode(&mut config, plugin_manager, tmux, matches.get_flag("no-popup")).await
 }
@@ -95,6 +221,9 @@ async fn run_interactive_mode(
     tmux: TmuxClient,
     force_no_popup: bool,
 ) -> Result<()> {
+    // Outcome of the last bulk action, shown above the list on the next round
+    let mut status: Option<String> = None;
+
     loop {
         // Get session context
         let context = tmux.get_session_context().await?;
@@ -102,67 +231,160 @@ async fn run_interactive_mode(
         // Create UI interface
         let mut ui = FzfInterface::new(&plugin_manager)
             .with_preview_enabled(config.preview_enabled)
-            .with_force_no_popup(force_no_popup);
+            .with_force_no_popup(force_no_popup)
+            .with_header(status.take())
+            .with_pinned_sessions(config.pinned_sessions.clone())
+            .with_session_tags(config.session_tags.clone())
+            .with_project_roots(config.project_roots.clone())
+            .with_templates(config.templates.clone())
+            .with_worktree_location(config.worktrees.location.clone())
+            .with_worktree_naming(config.worktrees.naming.clone());
 
         // Show session selector
         let result = ui.show_session_selector(&context).await?;
 
-        if let Some(selection) = result.selection {
-            let session_name = ui.extract_session_name(&selection);
-
+        if result.selections.is_empty() {
+            let query = result.query.trim();
             match result.key.as_deref() {
-                Some("ctrl-x") => {
-                    // Kill session and restart selector
-                    if let Err(e) = ui.kill_session(&session_name, &context).await {
-                        eprintln!("Failed to kill session: {}", e);
-                        break;
+                // Enter on a query that matched nothing opens or creates it
+                None if !query.is_empty() => match ui.open_query(query, &context).await {
+                    Ok(()) => break,
+                    Err(e) => {
+                        status = Some(format!("Failed to open {}: {}", query, e));
+                        continue;
                     }
-                    continue; // Restart the selector
+                },
+                // User cancelled selection
+                None => break,
+                // These act on the list as a whole rather than on a selection
+                Some("ctrl-n" | "ctrl-p" | "ctrl-z" | "alt-x" | "alt-w") => {}
+                Some(_) => continue,
+            }
+        }
+
+        let session_names: Vec<String> = result
+            .selections
+            .iter()
+            .map(|selection| ui.extract_session_name(selection))
+            .collect();
+        let session_name = session_names.first().cloned().unwrap_or_default();
+
+        match result.key.as_deref() {
+            Some("ctrl-x") => {
+                // Kill every selected session (after confirmation if busy) and restart selector
+                let report = ui.kill_sessions(&session_names, &context).await?;
+                status = Some(report.summary("Killed"));
+                continue; // Restart the selector
+            }
+            Some("alt-l") => {
+                // Toggle protection on every selected session
+                let report = ui.toggle_protection(&session_names, &context).await?;
+                if !report.failed.is_empty() || !report.skipped.is_empty() {
+                    status = Some(report.summary("Updated protection on"));
                 }
-                Some("ctrl-r") => {
-                    // Rename session
-                    if let Err(e) = ui.rename_session(&session_name, &context).await {
-                        eprintln!("Failed to rename session: {}", e);
-                        break;
-                    }
-                    continue; // Restart the selector to show the renamed session
+                continue;
+            }
+            Some("alt-x") => {
+                // Offer idle and orphaned sessions for cleanup
+                let candidates = PruneRules::from_config(config).find_candidates(&tmux, &context).await?;
+                if candidates.is_empty() {
+                    status = Some("Nothing to prune".to_string());
+                } else {
+                    let report = ui.prune_sessions(&candidates, &context).await?;
+                    status = Some(report.summary("Pruned"));
                 }
-                Some("ctrl-s") => {
-                    if let Err(e) = ui.start_session(&session_name, &context).await {
-                        eprintln!("Failed to start session: {}", e);
-                        break;
-                    }
-                    continue; // Restart the selector to show the new session
+                continue;
+            }
+            Some("ctrl-z") => {
+                // Restore the most recently killed session
+                match ui.undo_kill().await {
+                    Ok(restored) => status = Some(format!("Restored {}", restored.join(", "))),
+                    Err(e) => status = Some(format!("Failed to restore session: {}", e)),
                 }
-                Some("ctrl-n") => {
-                    // Create new session
-                    if let Err(e) = ui.create_new_session(&context).await {
-                        eprintln!("Failed to create new session: {}", e);
-                        break;
+                continue; // Restart the selector to show the restored session
+            }
+            Some("ctrl-r") => {
+                // Rename session through its plugin; pins and tags follow the new name
+                match ui.rename_session(&session_name, &context).await {
+                    Ok(Some(renamed)) => {
+                        config.rename_session(&session_name, &renamed.new_name).await?;
+                        status = renamed.warning;
                     }
-                    continue; // Restart the selector to show the new session
+                    Ok(None) => {}
+                    Err(e) => status = Some(format!("Failed to rename {}: {}", session_name, e)),
                 }
-                Some("ctrl-p") => {
-                    // Toggle preview and restart
-                    config.toggle_preview().await?;
-                    continue; // Restart with new preview state
+                continue; // Restart the selector to show the renamed session
+            }
+            Some("ctrl-s") => {
+                // Start every selected session in the background
+                let report = ui.start_sessions(&session_names, &context).await?;
+                status = Some(report.summary("Started"));
+                continue; // Restart the selector to show the new sessions
+            }
+            Some("ctrl-t") => {
+                // Toggle a tag on every selected session
+                if let Some(tag) = ui.prompt_for_tag().await? {
+                    config.toggle_tag(&session_names, tag.trim_start_matches('#')).await?;
                 }
-                _ => {
-                    // Switch to session
-                    ui.switch_to_session(&session_name, &context).await?;
-                    break;
+                continue;
+            }
+            Some("alt-p") => {
+                // Toggle the pin on every selected session
+                config.toggle_pins(&session_names).await?;
+                continue;
+            }
+            Some("ctrl-n") => {
+                // Create new session through the directory/name/template wizard
+                match ui.create_new_session(&context).await {
+                    Ok(Some(session_name)) => status = Some(format!("Created {}", session_name)),
+                    Ok(None) => {}
+                    Err(e) => status = Some(format!("Failed to create session: {}", e)),
                 }
+                continue; // Restart the selector to show the new session
+            }
+            Some("alt-d") => {
+                // Kill the selected worktree session and remove the worktree from disk
+                match ui.remove_worktree(&session_name, &context).await {
+                    Ok(removed) => status = removed,
+                    Err(e) => status = Some(format!("Failed to remove {}: {}", session_name, e)),
+                }
+                continue;
+            }
+            Some("alt-r") => {
+                // Add the windows the selected tmuxinator session lacks
+                match ui.reconcile_project(&session_name, &context).await {
+                    Ok(reconciled) => status = reconciled,
+                    Err(e) => status = Some(format!("Failed to reconcile {}: {}", session_name, e)),
+                }
+                continue;
+            }
+            Some("alt-w") => {
+                // Add a worktree to the selected session's repository and switch to it
+                let repo_session = session_names.first().or(context.current_session.as_ref());
+                match ui.create_worktree(repo_session.map(String::as_str), &context).await {
+                    Ok(Some(_)) => break,
+                    Ok(None) => {}
+                    Err(e) => status = Some(format!("Failed to create worktree: {}", e)),
+                }
+                continue;
+            }
+            Some("ctrl-p") => {
+                // Toggle preview and restart
+                config.toggle_preview().await?;
+                continue; // Restart with new preview state
+            }
+            _ => {
+                // Switch to session
+                ui.switch_to_session(&session_name, &context).await?;
+                break;
             }
-        } else {
-            // User cancelled selection
-            break;
         }
     }
 
     Ok(())
 }
 
-async fn doctor_command(plugin_manager: &PluginManager) -> Result<()> {
+async fn doctor_command(plugin_manager: &PluginManager, config: &Config, tmux: &TmuxClient) -> Result<()> {
     println!("tmux-session-manager doctor");
     println!("==========================");
     println!();
@@ -178,11 +400,18 @@ async fn doctor_command(plugin_manager: &PluginManager) -> Result<()> {
     }
 
     let mut error_count = 0;
+    let mut disabled_count = 0;
     let all_dependencies = vec!["tmux", "fzf"]; // Core dependencies
 
     for (name, description, _priority) in &plugins {
-        // TODO: Get plugin dependencies and check them
-        println!("\x1b[1;32m✓ {}\x1b[0m: {}", name, description);
+        let missing = plugin_manager.missing_dependencies(name).await;
+        if missing.is_empty() {
+            println!("\x1b[1;32m✓ {}\x1b[0m: {}", name, description);
+        } else {
+            // Plugins without their tools are skipped rather than failing the picker
+            println!("\x1b[1;33m! {}\x1b[0m: {} (disabled, {} not found)", name, description, missing.join(", "));
+            disabled_count += 1;
+        }
     }
 
     println!();
@@ -198,10 +427,29 @@ async fn doctor_command(plugin_manager: &PluginManager) -> Result<()> {
         }
     }
 
+    // Stale worktrees don't break anything, so they are warnings rather than issues
+    let context = tmux.get_session_context().await.unwrap_or_default();
+    let stale = WorktreePlugin::with_settings(&config.worktrees).find_stale(&context).await;
+    if !stale.is_empty() {
+        println!();
+        println!("Worktrees:");
+        println!("----------");
+        for line in stale.describe() {
+            println!("\x1b[1;33m! {}\x1b[0m", line);
+        }
+        println!("Run `tmux-session-manager clean-worktrees` to clean up");
+    }
+
     println!();
     println!("Summary:");
     println!("--------");
     println!("Plugins loaded: {}", plugins.len());
+    if disabled_count > 0 {
+        println!("Plugins disabled: {}", disabled_count);
+    }
+    if !stale.is_empty() {
+        println!("Stale worktree warnings: {}", stale.describe().len());
+    }
 
     if error_count == 0 {
         println!("\x1b[1;32mAll systems operational!\x1b[0m");
@@ -275,6 +523,133 @@ async fn generate_preview_command(plugin_manager: &PluginManager, tmux: &TmuxCli
     Ok(())
 }
 
+async fn undo_kill_command(tmux: &TmuxClient, count: usize) -> Result<()> {
+    let report = TrashBin::new().restore_latest(tmux, count).await?;
+    for session_name in &report.restored {
+        println!("Restored session: {}", session_name);
+    }
+    for (session_name, error) in &report.failed {
+        eprintln!("Failed to restore {}: {}", session_name, error);
+    }
+
+    if !report.failed.is_empty() {
+        return Err(anyhow::anyhow!("Failed to restore {} session(s)", report.failed.len()));
+    }
+    Ok(())
+}
+
+async fn kill_command(plugin_manager: &PluginManager, tmux: &TmuxClient, session_names: &[String], force: bool) -> Result<()> {
+    let context = tmux.get_session_context().await?;
+    let ui = FzfInterface::new(plugin_manager);
+    let report = ui.kill_selected(session_names, &context, force).await?;
+
+    println!("{}", report.summary("Killed"));
+    if !report.failed.is_empty() {
+        return Err(anyhow::anyhow!("Failed to kill {} session(s)", report.failed.len()));
+    }
+    Ok(())
+}
+
+async fn open_command(plugin_manager: &PluginManager, tmux: &TmuxClient, path: Option<&str>) -> Result<()> {
+    let dir = match path {
+        Some(path) => directories::expand_home(path),
+        None => std::env::current_dir()?,
+    };
+    let dir = dir
+        .canonicalize()
+        .map_err(|e| anyhow::anyhow!("Cannot open {}: {}", dir.display(), e))?;
+    if !dir.is_dir() {
+        return Err(anyhow::anyhow!("Not a directory: {}", dir.display()));
+    }
+
+    // Outside tmux there may be no server yet, and so no sessions to match
+    let context = tmux
+        .get_session_context()
+        .await
+        .unwrap_or_else(|_| SessionContext::new().with_working_dir(std::env::current_dir().ok()));
+    FzfInterface::new(plugin_manager).open_directory(&dir, &context).await
+}
+
+async fn prune_command(
+    plugin_manager: &PluginManager,
+    tmux: &TmuxClient,
+    rules: &PruneRules,
+    dry_run: bool,
+    assume_yes: bool,
+    force_no_popup: bool,
+) -> Result<()> {
+    let context = tmux.get_session_context().await?;
+    let candidates = rules.find_candidates(tmux, &context).await?;
+
+    if candidates.is_empty() {
+        println!("Nothing to prune");
+        return Ok(());
+    }
+
+    let (header, rows) = format_table(&candidates);
+    println!("{}", header);
+    for row in &rows {
+        println!("{}", row);
+    }
+
+    if dry_run {
+        return Ok(());
+    }
+
+    let ui = FzfInterface::new(plugin_manager).with_force_no_popup(force_no_popup);
+    let report = if assume_yes {
+        let session_names: Vec<String> = candidates.iter().map(|c| c.session_name.clone()).collect();
+        ui.kill_selected(&session_names, &context, false).await?
+    } else {
+        ui.prune_sessions(&candidates, &context).await?
+    };
+
+    println!();
+    println!("{}", report.summary("Pruned"));
+    Ok(())
+}
+
+async fn clean_worktrees_command(
+    plugin_manager: &PluginManager,
+    config: &Config,
+    tmux: &TmuxClient,
+    dry_run: bool,
+    assume_yes: bool,
+    force_no_popup: bool,
+) -> Result<()> {
+    let context = tmux.get_session_context().await.unwrap_or_default();
+    let plugin = WorktreePlugin::with_settings(&config.worktrees);
+    let stale = plugin.find_stale(&context).await;
+
+    if stale.is_empty() {
+        println!("No stale worktrees");
+        return Ok(());
+    }
+
+    let findings = stale.describe();
+    for line in &findings {
+        println!("{}", line);
+    }
+    if dry_run {
+        return Ok(());
+    }
+
+    let ui = FzfInterface::new(plugin_manager).with_force_no_popup(force_no_popup);
+    if !assume_yes && !ui.confirm("Prune worktrees and kill orphaned sessions?", &findings).await? {
+        return Ok(());
+    }
+
+    plugin.prune_stale(&stale).await?;
+    println!("Pruned {} worktree(s)", stale.prunable.len());
+
+    let session_names: Vec<String> = stale.orphaned_sessions.iter().map(|(name, _)| name.clone()).collect();
+    if !session_names.is_empty() {
+        let report = ui.kill_selected(&session_names, &context, false).await?;
+        println!("{}", report.summary("Killed"));
+    }
+    Ok(())
+}
+
 async fn help_preview_command() -> Result<()> {
     // Create a temporary PluginManager to get help text
     let plugin_manager = PluginManager::new();
@@ -283,10 +658,21 @@ async fn help_preview_command() -> Result<()> {
     println!(r#"Session Switcher Help
 
 Keybindings:
-  Enter    - Switch to session
-  Ctrl-x   - Kill session
+  Enter    - Switch to session (with no match: open the typed path,
+             project or name, creating the session if needed)
+  Tab      - Select multiple sessions
+  Ctrl-x   - Kill selected sessions (asks first if they run programs)
+  Ctrl-z   - Restore the last killed session
+  Alt-x    - Prune idle and orphaned sessions
+  Alt-l    - Toggle protection against kills on selected sessions
   Ctrl-r   - Rename session
-  Ctrl-n   - Create new session
+  Ctrl-s   - Start selected sessions in the background
+  Ctrl-t   - Toggle a tag on selected sessions
+  Alt-p    - Toggle pin on selected sessions
+  Ctrl-n   - Create new session (directory, name, template)
+  Alt-w    - Create a worktree from a branch and switch to it
+  Alt-d    - Kill a worktree session and remove the worktree
+  Alt-r    - Add the windows a tmuxinator session lacks
   Ctrl-p   - Toggle preview
   Ctrl-d   - Page down in preview
   Ctrl-u   - Page up in preview
@@ -305,7 +691,12 @@ Navigation:
   Esc         - Exit without selection
 
 Additional Commands:
-  --doctor - Check plugin health"#);
+  --doctor         - Check plugin health
+  kill [--force] S - Kill sessions (--force for protected ones)
+  undo-kill [N]    - Restore the N most recently killed sessions
+  open [PATH]      - Switch to or create the session for a directory
+  prune            - Kill idle, empty or orphaned sessions
+  clean-worktrees  - Prune deleted worktrees and kill their sessions"#);
     Ok(())
 }
 
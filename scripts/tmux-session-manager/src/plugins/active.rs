@@ -1,7 +1,9 @@
 use anyhow::Result;
 use async_trait::async_trait;
+use std::path::Path;
 
 use crate::core::{
+    git,
     session::{is_floating_session, SessionContext, SessionItem, SessionMetadata},
     tmux::TmuxClient,
 };
@@ -11,12 +13,27 @@ pub struct ActivePlugin {
     tmux: TmuxClient,
 }
 
+impl Default for ActivePlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ActivePlugin {
     pub fn new() -> Self {
         Self {
             tmux: TmuxClient::new(),
         }
     }
+
+    /// Plugin recorded in the session's `@tsm_plugin` option when it was created
+    fn find_owner<'a>(session_name: &str, context: &'a SessionContext) -> Option<&'a str> {
+        context
+            .all_tmux_sessions
+            .iter()
+            .find(|session| session.name == session_name)
+            .and_then(|session| session.options.plugin.as_deref())
+    }
 }
 
 #[async_trait]
@@ -81,7 +98,7 @@ impl SessionPlugin for ActivePlugin {
         Ok(sessions)
     }
 
-    async fn resolve(&self, session_name: &str, _context: &SessionContext) -> Result<SessionMetadata> {
+    async fn resolve(&self, session_name: &str, context: &SessionContext) -> Result<SessionMetadata> {
         if self.tmux.has_session(session_name).await {
             let session_path = self.tmux.get_session_path(session_name).await?;
 
@@ -91,19 +108,11 @@ impl SessionPlugin for ActivePlugin {
             if let Some(path) = session_path {
                 metadata = metadata.with_path(path.clone());
 
-                // Check if session is in a worktree
-                let git_file = std::path::Path::new(&path).join(".git");
-                if git_file.exists() {
-                    if let Ok(contents) = tokio::fs::read_to_string(&git_file).await {
-                        if contents.contains("gitdir:") {
-                            metadata = metadata.with_property("in_worktree".to_string(), "true".to_string());
-                        } else {
-                            metadata = metadata.with_property("in_worktree".to_string(), "false".to_string());
-                        }
-                    }
-                } else {
-                    metadata = metadata.with_property("in_worktree".to_string(), "false".to_string());
-                }
+                let in_worktree = match Self::find_owner(session_name, context) {
+                    Some(owner) => owner == "worktree",
+                    None => git::is_worktree_dir(Path::new(&path)).await,
+                };
+                metadata = metadata.with_property("in_worktree".to_string(), in_worktree.to_string());
             }
 
             Ok(metadata)
@@ -122,21 +131,19 @@ impl SessionPlugin for ActivePlugin {
         // Check if it's current session
         let is_current = context.current_session.as_ref() == Some(&session_name.to_string());
 
-        if is_active || is_current {
-            // Additional check: if it's a worktree session, let the worktree plugin handle it
-            // We can do a quick check by seeing if the session path has a .git file (worktree indicator)
-            if self.tmux.has_session(session_name).await {
-                if let Ok(Some(path)) = self.tmux.get_session_path(session_name).await {
-                    let git_file = std::path::Path::new(&path).join(".git");
-                    if git_file.is_file() {
-                        // This is a worktree, let the worktree plugin handle it
-                        return false;
-                    }
-                }
-            }
-            true
-        } else {
-            false
+        if !(is_active || is_current) {
+            return false;
+        }
+
+        // Sessions stamped by another plugin (worktree, tmuxinator) belong to that plugin
+        if let Some(owner) = Self::find_owner(session_name, context) {
+            return owner == self.name();
+        }
+
+        // Unstamped sessions in a worktree (.git file) are left to the worktree plugin
+        match self.tmux.get_session_path(session_name).await {
+            Ok(Some(path)) => !git::is_worktree_dir(Path::new(&path)).await,
+            _ => true,
         }
     }
 
@@ -0,0 +1,133 @@
+use anyhow::{anyhow, Result};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use tokio::task::JoinSet;
+
+use crate::core::naming;
+use crate::core::session::{SessionContext, SessionMetadata, SessionStamp, TmuxSession};
+use crate::core::tmux::{TmuxClient, PATH_OPTION};
+
+/// Session handling shared by the plugins whose sessions each live in one checkout of a
+/// repository: git worktrees and jj workspaces. Picker entries are either a running session's
+/// tmux name or the checkout identity its session is created under.
+pub struct CheckoutSessions {
+    tmux: TmuxClient,
+    plugin_name: &'static str,
+    /// What a checkout is called in messages, e.g. "worktree"
+    kind: &'static str,
+}
+
+impl CheckoutSessions {
+    pub fn new(plugin_name: &'static str, kind: &'static str) -> Self {
+        Self {
+            tmux: TmuxClient::new(),
+            plugin_name,
+            kind,
+        }
+    }
+
+    /// Directory a session belongs to; `@tsm_path` survives renames and `cd`s inside the session
+    pub fn session_dir(session: &TmuxSession) -> Option<String> {
+        session.options.path.clone().or_else(|| session.current_path.clone())
+    }
+
+    /// The running session a picker entry stands for
+    pub fn find_session<'a>(session_name: &str, sessions: &'a [TmuxSession]) -> Option<&'a TmuxSession> {
+        sessions
+            .iter()
+            .find(|session| session.name == session_name)
+            .or_else(|| naming::find_session(session_name, sessions))
+    }
+
+    /// Configured repositories, the current directory and every directory a session is open in
+    pub fn candidate_dirs(repos: &[PathBuf], current: Option<PathBuf>, context: &SessionContext) -> Vec<PathBuf> {
+        repos
+            .iter()
+            .cloned()
+            .chain(current)
+            .chain(context.all_tmux_sessions.iter().filter_map(Self::session_dir).map(PathBuf::from))
+            .collect()
+    }
+
+    /// The session for a picker entry, created in its checkout under a valid, unused tmux
+    /// name that maps back to it when none is running
+    pub async fn ensure_session(&self, session_name: &str, metadata: &SessionMetadata) -> Result<String> {
+        let path = metadata.path.as_ref()
+            .ok_or_else(|| anyhow!("No {} path found for session: {}", self.kind, session_name))?;
+
+        if !Path::new(path).exists() {
+            return Err(anyhow!("The {} path does not exist: {}", self.kind, path));
+        }
+
+        let sessions = self.tmux.list_all_sessions().await?;
+        if let Some(session) = Self::find_session(session_name, &sessions) {
+            return Ok(session.name.clone());
+        }
+
+        let tmux_session_name = naming::session_name_for(session_name, &sessions);
+        self.tmux.new_session(&tmux_session_name, Some(path)).await?;
+
+        let stamp = SessionStamp::new(self.plugin_name)
+            .with_path(path)
+            .with_identity(session_name);
+        self.tmux.stamp_session(&tmux_session_name, &stamp).await?;
+
+        Ok(tmux_session_name)
+    }
+
+    /// Rename a session, recording its checkout so discovery keeps mapping it to the new name
+    pub async fn rename(&self, old_name: &str, new_name: &str, metadata: &SessionMetadata) -> Result<Option<String>> {
+        self.tmux.rename_session(old_name, new_name).await?;
+
+        if let Some(path) = &metadata.path {
+            self.tmux.set_session_option(new_name, PATH_OPTION, path).await?;
+        }
+
+        Ok(None)
+    }
+}
+
+/// Look up the repository of each directory concurrently, keeping one per repository in the
+/// order the directories came in. `checkout_of` finds the checkout holding a directory without
+/// running anything, so directories outside one are skipped and each checkout is looked up once;
+/// `lookup` returns the repository with a key identifying it (its main checkout).
+pub async fn collect_repos<R, F, Fut>(dirs: Vec<PathBuf>, checkout_of: impl Fn(&Path) -> Option<&Path>, lookup: F) -> Vec<R>
+where
+    R: Send + 'static,
+    F: Fn(PathBuf) -> Fut,
+    Fut: Future<Output = Option<(PathBuf, R)>> + Send + 'static,
+{
+    let mut checkouts: Vec<PathBuf> = Vec::new();
+    for dir in &dirs {
+        if let Some(checkout) = checkout_of(dir) {
+            if !checkouts.iter().any(|seen| seen == checkout) {
+                checkouts.push(checkout.to_path_buf());
+            }
+        }
+    }
+
+    let mut lookups = JoinSet::new();
+    for (index, checkout) in checkouts.into_iter().enumerate() {
+        let found = lookup(checkout);
+        lookups.spawn(async move { (index, found.await) });
+    }
+
+    let mut found = Vec::new();
+    while let Some(lookup) = lookups.join_next().await {
+        if let Ok((index, Some(repo))) = lookup {
+            found.push((index, repo));
+        }
+    }
+    found.sort_by_key(|(index, _)| *index);
+
+    let mut repos: Vec<R> = Vec::new();
+    let mut keys: Vec<PathBuf> = Vec::new();
+    for (_, (key, repo)) in found {
+        if !keys.contains(&key) {
+            keys.push(key);
+            repos.push(repo);
+        }
+    }
+
+    repos
+}
@@ -0,0 +1,273 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::path::{Path, PathBuf};
+
+use crate::config::WorktreeSettings;
+use crate::core::session::{SessionContext, SessionItem, SessionMetadata, TmuxSession};
+use crate::core::{directories, jj, naming};
+use crate::core::tmux::TmuxClient;
+use crate::plugins::checkout::{self, CheckoutSessions};
+use crate::plugins::SessionPlugin;
+
+/// Sessions for jj (Jujutsu) workspaces, the jj counterpart of git worktrees. Shares the
+/// `worktrees` settings: the same repositories are listed and sessions follow the same naming.
+pub struct JjPlugin {
+    tmux: TmuxClient,
+    sessions: CheckoutSessions,
+    /// Repositories listed even when none of their workspaces has a session
+    repos: Vec<PathBuf>,
+    naming: String,
+}
+
+/// The workspaces of one repository, named after its default workspace
+struct RepoWorkspaces {
+    name: String,
+    workspaces: Vec<jj::Workspace>,
+}
+
+impl RepoWorkspaces {
+    fn contains(&self, dir: &Path) -> bool {
+        self.workspaces
+            .iter()
+            .filter_map(|workspace| workspace.path.as_deref())
+            .any(|path| dir.starts_with(path))
+    }
+
+    /// Workspaces besides the default one that could be located on disk
+    fn secondary(&self) -> impl Iterator<Item = (&jj::Workspace, &Path)> {
+        self.workspaces
+            .iter()
+            .filter(|workspace| workspace.name != "default")
+            .filter_map(|workspace| workspace.path.as_deref().map(|path| (workspace, path)))
+    }
+}
+
+impl Default for JjPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JjPlugin {
+    pub fn new() -> Self {
+        Self::with_settings(&WorktreeSettings::default())
+    }
+
+    pub fn with_settings(settings: &WorktreeSettings) -> Self {
+        Self {
+            tmux: TmuxClient::new(),
+            sessions: CheckoutSessions::new("jj", "workspace"),
+            repos: settings.repos.iter().map(|repo| directories::expand_home(repo)).collect(),
+            naming: settings.naming.clone(),
+        }
+    }
+
+    /// The directory of the current session, or the working directory outside tmux
+    async fn current_dir(&self, context: &SessionContext) -> Option<PathBuf> {
+        match &context.current_session {
+            Some(session_name) => self.tmux.get_session_path(session_name).await.ok().flatten().map(PathBuf::from),
+            None => context.working_dir.clone(),
+        }
+    }
+
+    /// The workspace directory holding `dir`, found without running jj
+    fn workspace_of(dir: &Path) -> Option<&Path> {
+        dir.ancestors().find(|ancestor| ancestor.join(".jj").is_dir())
+    }
+
+    /// Workspaces of every configured repository, the current one and each one a session is open in
+    async fn collect_repos(&self, context: &SessionContext) -> Vec<RepoWorkspaces> {
+        let dirs = CheckoutSessions::candidate_dirs(&self.repos, self.current_dir(context).await, context);
+
+        checkout::collect_repos(dirs, Self::workspace_of, |dir| async move {
+            let root = jj::repo_root(&dir).await?;
+            let workspaces = jj::list_workspaces(&root).await.ok()?;
+            let name = root
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| root.to_string_lossy().to_string());
+            Some((root, RepoWorkspaces { name, workspaces }))
+        })
+        .await
+    }
+
+    /// Picker name and session identity of a workspace, from the worktree naming pattern
+    /// with the workspace name standing in for the branch
+    fn workspace_identity(&self, repo: &RepoWorkspaces, workspace: &jj::Workspace) -> String {
+        naming::checkout_identity(&self.naming, &repo.name, &workspace.name, &workspace.name)
+    }
+
+    /// Stamped sessions say exactly who owns them; older ones count when they sit in a
+    /// secondary workspace, leaving the default workspace to the plain session plugins
+    fn is_workspace_session(&self, session: &TmuxSession, session_dir: &str) -> bool {
+        match session.options.owned_by(self.name()) {
+            Some(owned) => owned,
+            None => Self::workspace_of(Path::new(session_dir)).is_some_and(jj::is_secondary_workspace),
+        }
+    }
+}
+
+#[async_trait]
+impl SessionPlugin for JjPlugin {
+    fn name(&self) -> &str {
+        "jj"
+    }
+
+    fn description(&self) -> &str {
+        "Jujutsu workspace sessions"
+    }
+
+    fn priority(&self) -> u32 {
+        6 // Just below git worktrees, above plain active sessions
+    }
+
+    fn dependencies(&self) -> Vec<&str> {
+        vec!["jj"]
+    }
+
+    async fn discover(&self, context: &SessionContext) -> Result<Vec<SessionItem>> {
+        let mut sessions = Vec::new();
+        let repos = self.collect_repos(context).await;
+
+        // First, running sessions inside a workspace
+        let mut discovered_paths = Vec::new();
+        for tmux_session in &context.all_tmux_sessions {
+            let Some(session_dir) = CheckoutSessions::session_dir(tmux_session) else {
+                continue;
+            };
+            if !self.is_workspace_session(tmux_session, &session_dir) {
+                continue;
+            }
+
+            let is_current = context.current_session.as_ref() == Some(&tmux_session.name);
+            let metadata = SessionMetadata::new("jj".to_string())
+                .with_exists(true)
+                .with_path(session_dir.clone());
+
+            let mut session_item = SessionItem::new(
+                tmux_session.name.clone(),
+                "jj".to_string(),
+                self.priority(),
+                metadata,
+            ).with_current(is_current)
+              .with_active(true)
+              .with_timestamp(tmux_session.last_attached);
+
+            if let Some(repo) = repos.iter().find(|repo| repo.contains(Path::new(&session_dir))) {
+                session_item = session_item.with_group(repo.name.clone());
+            }
+
+            sessions.push(session_item);
+            discovered_paths.push(PathBuf::from(session_dir));
+        }
+
+        // Then the workspaces of every known repository that don't have sessions yet
+        for repo in &repos {
+            for (workspace, path) in repo.secondary() {
+                let identity = self.workspace_identity(repo, workspace);
+                if naming::find_session(&identity, &context.all_tmux_sessions).is_some() ||
+                   discovered_paths.iter().any(|discovered| discovered == path) {
+                    continue;
+                }
+
+                let metadata = SessionMetadata::new("jj".to_string())
+                    .with_exists(false)
+                    .with_path(path.to_string_lossy().to_string());
+
+                sessions.push(SessionItem::new(
+                    identity,
+                    "jj".to_string(),
+                    self.priority(),
+                    metadata,
+                ).with_active(false)
+                  .with_group(repo.name.clone())
+                  .with_timestamp(DateTime::<Utc>::UNIX_EPOCH));
+            }
+        }
+
+        Ok(sessions)
+    }
+
+    async fn resolve(&self, session_name: &str, context: &SessionContext) -> Result<SessionMetadata> {
+        if let Some(running_session) = CheckoutSessions::find_session(session_name, &context.all_tmux_sessions) {
+            if let Some(session_dir) = CheckoutSessions::session_dir(running_session) {
+                if self.is_workspace_session(running_session, &session_dir) {
+                    return Ok(SessionMetadata::new("jj".to_string())
+                        .with_exists(true)
+                        .with_path(session_dir));
+                }
+            }
+        }
+
+        for repo in self.collect_repos(context).await {
+            if let Some((_, path)) = repo
+                .secondary()
+                .find(|(workspace, _)| self.workspace_identity(&repo, workspace) == session_name)
+            {
+                return Ok(SessionMetadata::new("jj".to_string())
+                    .with_exists(false)
+                    .with_path(path.to_string_lossy().to_string()));
+            }
+        }
+
+        Err(anyhow!("jj workspace not found for session: {}", session_name))
+    }
+
+    async fn switch(&self, session_name: &str, metadata: &SessionMetadata) -> Result<()> {
+        let tmux_session_name = self.sessions.ensure_session(session_name, metadata).await?;
+        self.tmux.switch_or_attach(&tmux_session_name).await
+    }
+
+    async fn start(&self, session_name: &str, metadata: &SessionMetadata) -> Result<()> {
+        self.sessions.ensure_session(session_name, metadata).await.map(|_| ())
+    }
+
+    async fn rename(&self, old_name: &str, new_name: &str, metadata: &SessionMetadata) -> Result<Option<String>> {
+        self.sessions.rename(old_name, new_name, metadata).await
+    }
+
+    async fn preview(&self, session_name: &str, metadata: &SessionMetadata) -> Result<String> {
+        let mut preview = format!("\x1b[0;35mjj Workspace: {}\x1b[0m\n\n", session_name);
+
+        let Some(workspace_path) = &metadata.path else {
+            preview.push_str("Workspace path not found\n");
+            return Ok(preview);
+        };
+        preview.push_str(&format!("\x1b[1;33mWorkspace path:\x1b[0m\n{}\n\n", workspace_path));
+
+        let dir = Path::new(workspace_path);
+        if !dir.exists() {
+            preview.push_str("Workspace path does not exist\n");
+            return Ok(preview);
+        }
+
+        let description = jj::working_copy_description(dir).await;
+        preview.push_str(&format!(
+            "\x1b[1;33mWorking-copy change:\x1b[0m\n{}\n\n",
+            description.as_deref().unwrap_or("(no description set)")
+        ));
+
+        match jj::log(dir, 10).await {
+            Ok(log) => {
+                preview.push_str("\x1b[1;33mjj log:\x1b[0m\n");
+                preview.push_str(&log);
+                preview.push('\n');
+            }
+            Err(e) => preview.push_str(&format!("\x1b[0;31m{}\x1b[0m\n", e)),
+        }
+
+        if !metadata.exists {
+            preview.push_str("\n\x1b[1;33mSession will be created when selected\x1b[0m\n");
+        }
+
+        Ok(preview)
+    }
+
+    fn get_help_text(&self) -> Vec<String> {
+        vec![
+            "\x1b[0;35m●\x1b[0m - Active jj workspace session".to_string(),
+            "\x1b[0;35m○\x1b[0m - Inactive jj workspace".to_string(),
+        ]
+    }
+}
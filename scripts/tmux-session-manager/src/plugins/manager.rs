@@ -4,40 +4,111 @@ use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
 
-use crate::core::session::{SessionContext, SessionItem};
+use crate::config::{matches_any_pattern, ProjectEntry, RepoScanSettings, WorktreeSettings};
+use crate::core::session::{validate_session_name, SessionContext, SessionItem};
+use crate::core::template::SessionTemplate;
+use crate::core::tmux::TmuxClient;
+use crate::core::trash::TrashBin;
 use crate::plugins::{
     active::ActivePlugin,
     recent::RecentPlugin,
     worktree::WorktreePlugin,
+    jj::JjPlugin,
+    project::ProjectPlugin,
+    repo::RepoPlugin,
+    zoxide::ZoxidePlugin,
     scratch::ScratchPlugin,
     tmuxinator::TmuxinatorPlugin,
     DynamicPlugin, PluginFactory, SessionPlugin,
 };
 
+/// Plugins that only mirror tmux's session list rather than owning what a session is for
+const GENERIC_PLUGINS: &[&str] = &["recent", "active", "scratch"];
+
 pub struct PluginManager {
     builtin_plugins: Vec<Box<dyn SessionPlugin>>,
     dynamic_plugins: Vec<Arc<Box<dyn DynamicPlugin>>>,
     _libraries: Vec<Library>, // Keep libraries alive
+    protected_patterns: Vec<String>,
+}
+
+impl Default for PluginManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl PluginManager {
     pub fn new() -> Self {
-        let mut builtin_plugins: Vec<Box<dyn SessionPlugin>> = Vec::new();
-
         // Register builtin plugins in priority order
-        builtin_plugins.push(Box::new(RecentPlugin::new()));
-        builtin_plugins.push(Box::new(WorktreePlugin::new()));
-        builtin_plugins.push(Box::new(ActivePlugin::new()));
-        builtin_plugins.push(Box::new(TmuxinatorPlugin::new()));
-        builtin_plugins.push(Box::new(ScratchPlugin::new()));
+        let builtin_plugins: Vec<Box<dyn SessionPlugin>> = vec![
+            Box::new(RecentPlugin::new()),
+            Box::new(WorktreePlugin::new()),
+            Box::new(JjPlugin::new()),
+            Box::new(ProjectPlugin::new()),
+            Box::new(ActivePlugin::new()),
+            Box::new(TmuxinatorPlugin::new()),
+            Box::new(RepoPlugin::new()),
+            Box::new(ZoxidePlugin::new()),
+            Box::new(ScratchPlugin::new()),
+        ];
 
         Self {
             builtin_plugins,
             dynamic_plugins: Vec::new(),
             _libraries: Vec::new(),
+            protected_patterns: Vec::new(),
         }
     }
 
+    /// Session name patterns that refuse to be killed unless forced
+    pub fn with_protected_patterns(mut self, patterns: Vec<String>) -> Self {
+        self.protected_patterns = patterns;
+        self
+    }
+
+    /// Configured projects, which replace the empty project list registered by `new`
+    pub fn with_projects(self, projects: Vec<ProjectEntry>, templates: Vec<SessionTemplate>) -> Self {
+        self.with_builtin(ProjectPlugin::new().with_projects(projects).with_templates(templates))
+    }
+
+    /// Roots the repo plugin scans; it lists nothing when there are none
+    pub fn with_repo_scan(self, settings: &RepoScanSettings) -> Self {
+        self.with_builtin(RepoPlugin::with_settings(settings))
+    }
+
+    /// Repositories whose worktrees (and jj workspaces) are listed even without a session in them
+    pub fn with_worktree_settings(self, settings: &WorktreeSettings) -> Self {
+        self.with_builtin(WorktreePlugin::with_settings(settings))
+            .with_builtin(JjPlugin::with_settings(settings))
+    }
+
+    /// Directories, such as configured projects and repositories, checked for a project-local `.tmuxinator.yml`
+    pub fn with_tmuxinator_project_dirs(self, dirs: &[String]) -> Self {
+        self.with_builtin(TmuxinatorPlugin::new().with_project_dirs(dirs))
+    }
+
+    /// Swap in a configured instance of a builtin plugin, keeping its place in the lookup order
+    fn with_builtin(mut self, plugin: impl SessionPlugin + 'static) -> Self {
+        if let Some(slot) = self.builtin_plugins.iter_mut().find(|slot| slot.name() == plugin.name()) {
+            *slot = Box::new(plugin);
+        }
+        self
+    }
+
+    pub fn is_protected_by_pattern(&self, session_name: &str) -> bool {
+        matches_any_pattern(&self.protected_patterns, session_name)
+    }
+
+    /// Protected either through config patterns or the session's `@tsm_protected` option
+    pub fn is_protected(&self, session_name: &str, context: &SessionContext) -> bool {
+        self.is_protected_by_pattern(session_name)
+            || context
+                .all_tmux_sessions
+                .iter()
+                .any(|session| session.name == session_name && session.options.protected)
+    }
+
     pub async fn load_dynamic_plugins(&mut self, plugin_dir: &Path) -> Result<()> {
         if !plugin_dir.exists() {
             return Ok(());
@@ -151,9 +222,15 @@ impl PluginManager {
             }
         }
 
-        // Sort by priority, then by timestamp (most recent first)
+        for session in &mut all_sessions {
+            session.is_protected = self.is_protected(&session.name, context);
+        }
+
+        // Sort by priority and group, then by score and timestamp (highest and most recent first)
         all_sessions.sort_by(|a, b| {
             a.priority.cmp(&b.priority)
+                .then_with(|| a.group.cmp(&b.group))
+                .then_with(|| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal))
                 .then_with(|| b.timestamp.cmp(&a.timestamp))
         });
 
@@ -197,14 +274,94 @@ impl PluginManager {
         }
     }
 
-    pub async fn kill_session(&self, session_name: &str, context: &SessionContext) -> Result<()> {
-        if let Some(plugin) = self.find_plugin_for_session(session_name, context).await {
-            plugin.kill(session_name).await
+    /// Kill a session through its plugin; protected sessions are refused unless `force` is set
+    pub async fn kill_session(&self, session_name: &str, context: &SessionContext, force: bool) -> Result<()> {
+        if !force && self.is_protected(session_name, context) {
+            return Err(anyhow!("Session '{}' is protected", session_name));
+        }
+
+        let plugin = self
+            .find_plugin_for_session(session_name, context)
+            .await
+            .ok_or_else(|| anyhow!("No plugin found for session: {}", session_name))?;
+
+        // Saved to the trash bin first so `undo-kill` can bring it back; entries without a
+        // running session (a project, a repository) have nothing to save
+        let tmux = TmuxClient::new();
+        let trash = TrashBin::new();
+        let snapshot = if tmux.has_session(session_name).await {
+            let entry = trash
+                .snapshot(&tmux, session_name)
+                .await
+                .map_err(|e| anyhow!("Failed to snapshot '{}' before killing it: {}", session_name, e))?;
+            Some(entry)
         } else {
-            Err(anyhow!("No plugin found for session: {}", session_name))
+            None
+        };
+
+        // A session that is still running must not come back as a copy on `undo-kill`
+        if let Err(e) = plugin.kill(session_name).await {
+            if let Some(entry) = &snapshot {
+                let _ = trash.discard(entry).await;
+            }
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    /// The first plugin other than the generic ones that recognises a session or project name
+    pub async fn find_specific_plugin(&self, session_name: &str, context: &SessionContext) -> Option<&dyn SessionPlugin> {
+        let specific = self
+            .builtin_plugins
+            .iter()
+            .map(|plugin| plugin.as_ref())
+            .chain(self.dynamic_plugins.iter().map(|plugin| plugin.as_ref().as_ref() as &dyn SessionPlugin))
+            .filter(|plugin| !GENERIC_PLUGINS.contains(&plugin.name()));
+
+        for plugin in specific {
+            if plugin.can_handle(session_name, context).await {
+                return Some(plugin);
+            }
+        }
+
+        None
+    }
+
+    /// The plugin that owns a running session, preferring specific plugins (worktree, tmuxinator,
+    /// dynamic) over the generic ones that would otherwise claim every session first
+    async fn find_owner_for_session(&self, session_name: &str, context: &SessionContext) -> Option<&dyn SessionPlugin> {
+        match self.find_specific_plugin(session_name, context).await {
+            Some(plugin) => Some(plugin),
+            None => self.find_plugin_for_session(session_name, context).await,
         }
     }
 
+    /// Check a new session name against tmux's rules and the sessions already running
+    pub fn validate_new_name(&self, new_name: &str, context: &SessionContext) -> Result<()> {
+        validate_session_name(new_name)?;
+
+        if context.all_tmux_sessions.iter().any(|session| session.name == new_name) {
+            return Err(anyhow!("Session '{}' already exists", new_name));
+        }
+
+        Ok(())
+    }
+
+    /// Rename a running session through its owning plugin, returning any warning it raised
+    pub async fn rename_session(&self, old_name: &str, new_name: &str, context: &SessionContext) -> Result<Option<String>> {
+        if !context.all_tmux_sessions.iter().any(|session| session.name == old_name) {
+            return Err(anyhow!("Session '{}' is not running", old_name));
+        }
+        self.validate_new_name(new_name, context)?;
+
+        if let Some(plugin) = self.find_owner_for_session(old_name, context).await {
+            let metadata = plugin.resolve(old_name, context).await?;
+            plugin.rename(old_name, new_name, &metadata).await
+        } else {
+            Err(anyhow!("No plugin found for session: {}", old_name))
+        }
+    }
 
     pub async fn start_session(&self, session_name: &str, context: &SessionContext) -> Result<()> {
         if let Some(plugin) = self.find_plugin_for_session(session_name, context).await {
@@ -248,6 +405,26 @@ impl PluginManager {
         plugins
     }
 
+    /// The builtin or dynamic plugin registered under `plugin_name`
+    pub fn plugin(&self, plugin_name: &str) -> Option<&dyn SessionPlugin> {
+        self.builtin_plugins
+            .iter()
+            .map(|plugin| plugin.as_ref())
+            .chain(self.dynamic_plugins.iter().map(|plugin| plugin.as_ref().as_ref() as &dyn SessionPlugin))
+            .find(|plugin| plugin.name() == plugin_name)
+    }
+
+    /// External commands each plugin needs; plugins missing one are left out of discovery
+    pub async fn missing_dependencies(&self, plugin_name: &str) -> Vec<String> {
+        let mut missing = Vec::new();
+        for dep in self.plugin(plugin_name).map(|plugin| plugin.dependencies()).unwrap_or_default() {
+            if !self.command_exists(dep).await {
+                missing.push(dep.to_string());
+            }
+        }
+        missing
+    }
+
     pub fn get_all_help_text(&self) -> Vec<String> {
         let mut help_lines = Vec::new();
 
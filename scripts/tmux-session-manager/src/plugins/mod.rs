@@ -1,7 +1,12 @@
 pub mod traits;
 pub mod recent;
 pub mod active;
+pub mod checkout;
 pub mod worktree;
+pub mod jj;
+pub mod project;
+pub mod repo;
+pub mod zoxide;
 pub mod tmuxinator;
 pub mod scratch;
 pub mod manager;
@@ -0,0 +1,217 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+
+use crate::config::ProjectEntry;
+use crate::core::directories;
+use crate::core::naming;
+use crate::core::session::{SessionContext, SessionItem, SessionMetadata, SessionStamp, TmuxSession};
+use crate::core::template::SessionTemplate;
+use crate::core::tmux::TmuxClient;
+use crate::plugins::SessionPlugin;
+
+impl ProjectEntry {
+    fn dir(&self) -> String {
+        directories::expand_home(&self.path).to_string_lossy().to_string()
+    }
+
+    /// The running session for this project: the one created for it, or one already open in its directory
+    fn find_session<'a>(&self, sessions: &'a [TmuxSession]) -> Option<&'a TmuxSession> {
+        naming::find_session(&self.name, sessions)
+            .or_else(|| directories::find_session_for_dir(std::path::Path::new(&self.dir()), sessions))
+    }
+}
+
+pub struct ProjectPlugin {
+    tmux: TmuxClient,
+    projects: Vec<ProjectEntry>,
+    templates: Vec<SessionTemplate>,
+}
+
+impl Default for ProjectPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProjectPlugin {
+    pub fn new() -> Self {
+        Self {
+            tmux: TmuxClient::new(),
+            projects: Vec::new(),
+            templates: Vec::new(),
+        }
+    }
+
+    pub fn with_projects(mut self, projects: Vec<ProjectEntry>) -> Self {
+        self.projects = projects;
+        self
+    }
+
+    pub fn with_templates(mut self, templates: Vec<SessionTemplate>) -> Self {
+        self.templates = templates;
+        self
+    }
+
+    /// The project behind a picker entry, which is the project name or the name of its running session
+    fn find_project<'a>(&'a self, session_name: &str, sessions: &[TmuxSession]) -> Option<&'a ProjectEntry> {
+        self.projects
+            .iter()
+            .find(|project| project.name == session_name)
+            .or_else(|| {
+                self.projects.iter().find(|project| {
+                    project
+                        .find_session(sessions)
+                        .is_some_and(|session| session.name == session_name)
+                })
+            })
+    }
+
+    fn find_template(&self, project: &ProjectEntry) -> Result<Option<&SessionTemplate>> {
+        match &project.template {
+            Some(name) => self
+                .templates
+                .iter()
+                .find(|template| &template.name == name)
+                .map(Some)
+                .ok_or_else(|| anyhow!("Project '{}' uses unknown template '{}'", project.name, name)),
+            None => Ok(None),
+        }
+    }
+
+    /// Name of the project's running session, creating it first when there is none
+    async fn ensure_session(&self, session_name: &str) -> Result<String> {
+        let sessions = self.tmux.list_all_sessions().await.unwrap_or_default();
+        let project = self
+            .find_project(session_name, &sessions)
+            .ok_or_else(|| anyhow!("Project not found: {}", session_name))?;
+
+        if let Some(session) = project.find_session(&sessions) {
+            return Ok(session.name.clone());
+        }
+
+        // Check the template before creating anything, so a typo does not leave a bare session behind
+        let template = self.find_template(project)?;
+        let dir = project.dir();
+        let tmux_session_name = naming::session_name_for(&project.name, &sessions);
+
+        self.tmux.new_session(&tmux_session_name, Some(&dir)).await?;
+
+        let stamp = SessionStamp::new(self.name())
+            .with_path(&dir)
+            .with_project(&project.name)
+            .with_identity(&project.name);
+        self.tmux.stamp_session(&tmux_session_name, &stamp).await?;
+
+        if let Some(template) = template {
+            template.apply(&self.tmux, &tmux_session_name, &dir).await?;
+        }
+
+        Ok(tmux_session_name)
+    }
+}
+
+#[async_trait]
+impl SessionPlugin for ProjectPlugin {
+    fn name(&self) -> &str {
+        "project"
+    }
+
+    fn description(&self) -> &str {
+        "Projects listed in the config"
+    }
+
+    fn priority(&self) -> u32 {
+        8 // Above plain active sessions so running projects keep their entry
+    }
+
+    async fn discover(&self, context: &SessionContext) -> Result<Vec<SessionItem>> {
+        let mut sessions = Vec::new();
+
+        for project in &self.projects {
+            let running_session = project.find_session(&context.all_tmux_sessions);
+
+            let metadata = SessionMetadata::new("project".to_string())
+                .with_exists(running_session.is_some())
+                .with_path(project.dir());
+
+            // A running project is listed under its tmux name, which also hides its plain active entry
+            let mut session_item = SessionItem::new(
+                running_session.map_or_else(|| project.name.clone(), |session| session.name.clone()),
+                "project".to_string(),
+                self.priority(),
+                metadata,
+            ).with_active(running_session.is_some())
+              .with_tags(project.tags.clone());
+
+            if let Some(session) = running_session {
+                let is_current = context.current_session.as_ref() == Some(&session.name);
+                session_item = session_item
+                    .with_current(is_current)
+                    .with_timestamp(session.last_attached);
+            }
+
+            sessions.push(session_item);
+        }
+
+        Ok(sessions)
+    }
+
+    async fn resolve(&self, session_name: &str, context: &SessionContext) -> Result<SessionMetadata> {
+        let project = self
+            .find_project(session_name, &context.all_tmux_sessions)
+            .ok_or_else(|| anyhow!("Project not found: {}", session_name))?;
+
+        let mut metadata = SessionMetadata::new("project".to_string())
+            .with_exists(project.find_session(&context.all_tmux_sessions).is_some())
+            .with_path(project.dir())
+            .with_property("project".to_string(), project.name.clone());
+
+        if let Some(template) = &project.template {
+            metadata = metadata.with_property("template".to_string(), template.clone());
+        }
+
+        Ok(metadata)
+    }
+
+    async fn switch(&self, session_name: &str, _metadata: &SessionMetadata) -> Result<()> {
+        let tmux_session_name = self.ensure_session(session_name).await?;
+        self.tmux.switch_or_attach(&tmux_session_name).await
+    }
+
+    async fn start(&self, session_name: &str, _metadata: &SessionMetadata) -> Result<()> {
+        self.ensure_session(session_name).await.map(|_| ())
+    }
+
+    async fn preview(&self, session_name: &str, metadata: &SessionMetadata) -> Result<String> {
+        let project = metadata.get_property("project").map_or(session_name, String::as_str);
+        let mut preview = format!("\x1b[0;35m● Project: {}\x1b[0m\n\n", project);
+
+        if let Some(path) = &metadata.path {
+            preview.push_str(&format!("\x1b[1;33mPath:\x1b[0m {}\n", path));
+        }
+        if let Some(template) = metadata.get_property("template") {
+            preview.push_str(&format!("\x1b[1;33mTemplate:\x1b[0m {}\n", template));
+        }
+        preview.push('\n');
+
+        if !metadata.exists {
+            preview.push_str("\x1b[1;33mSession will be created when selected\x1b[0m\n");
+            return Ok(preview);
+        }
+
+        preview.push_str("\x1b[0;90m────────────────────────────────────────\x1b[0m\n");
+        match self.tmux.capture_pane(session_name).await {
+            Ok(content) => preview.push_str(&content),
+            Err(_) => preview.push_str("\x1b[0;31mCould not capture session content\x1b[0m\n"),
+        }
+
+        Ok(preview)
+    }
+
+    fn get_help_text(&self) -> Vec<String> {
+        vec![
+            "\x1b[0;35m●\x1b[0m - Configured project (running)".to_string(),
+            "\x1b[0;35m○\x1b[0m - Configured project (not running)".to_string(),
+        ]
+    }
+}
@@ -8,6 +8,12 @@ pub struct RecentPlugin {
     tmux: TmuxClient,
 }
 
+impl Default for RecentPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl RecentPlugin {
     pub fn new() -> Self {
         Self {
@@ -0,0 +1,157 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use tokio::process::Command;
+use tokio::sync::OnceCell;
+
+use crate::config::RepoScanSettings;
+use crate::core::directories;
+use crate::core::repo_scan::{FoundRepo, RepoScanner};
+use crate::core::session::{SessionContext, SessionItem, SessionMetadata};
+use crate::core::tmux::TmuxClient;
+use crate::plugins::SessionPlugin;
+
+pub struct RepoPlugin {
+    tmux: TmuxClient,
+    scanner: RepoScanner,
+    /// Scanned once per run; the picker loop and previews reuse the result
+    repos: OnceCell<Vec<FoundRepo>>,
+}
+
+impl Default for RepoPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RepoPlugin {
+    pub fn new() -> Self {
+        Self::with_settings(&RepoScanSettings::default())
+    }
+
+    pub fn with_settings(settings: &RepoScanSettings) -> Self {
+        Self {
+            tmux: TmuxClient::new(),
+            scanner: RepoScanner::new(settings),
+            repos: OnceCell::new(),
+        }
+    }
+
+    async fn repos(&self) -> &[FoundRepo] {
+        self.repos.get_or_init(|| self.scanner.scan()).await
+    }
+
+    async fn find_repo(&self, name: &str) -> Option<&FoundRepo> {
+        self.repos().await.iter().find(|repo| repo.name == name)
+    }
+
+    /// Shares the open command's rules: reuse a session already at the repo, else create one named after it
+    async fn ensure_session(&self, session_name: &str, metadata: &SessionMetadata) -> Result<String> {
+        let path = metadata
+            .path
+            .as_deref()
+            .ok_or_else(|| anyhow!("Repository path unknown: {}", session_name))?;
+
+        // Outside tmux there may be no server yet, and so no sessions to match
+        let context = self.tmux.get_session_context().await.unwrap_or_default();
+        directories::ensure_session_for_path(&self.tmux, path.as_ref(), &context).await
+    }
+
+    async fn current_branch(dir: &str) -> Option<String> {
+        let output = Command::new("git")
+            .args(["branch", "--show-current"])
+            .current_dir(dir)
+            .output()
+            .await
+            .ok()?;
+
+        let branch = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        (output.status.success() && !branch.is_empty()).then_some(branch)
+    }
+}
+
+#[async_trait]
+impl SessionPlugin for RepoPlugin {
+    fn name(&self) -> &str {
+        "repo"
+    }
+
+    fn description(&self) -> &str {
+        "Git repositories under the scan roots"
+    }
+
+    fn priority(&self) -> u32 {
+        60
+    }
+
+    fn dependencies(&self) -> Vec<&str> {
+        vec!["git"]
+    }
+
+    async fn discover(&self, context: &SessionContext) -> Result<Vec<SessionItem>> {
+        if !self.scanner.has_roots() {
+            return Ok(Vec::new());
+        }
+
+        let mut sessions = Vec::new();
+
+        for repo in self.repos().await {
+            // Repositories with a running session are already listed through it
+            if directories::find_session_for_dir(&repo.path, &context.all_tmux_sessions).is_some() {
+                continue;
+            }
+
+            let metadata = SessionMetadata::new("repo".to_string())
+                .with_exists(false)
+                .with_path(repo.path.to_string_lossy().to_string());
+
+            sessions.push(SessionItem::new(
+                repo.name.clone(),
+                "repo".to_string(),
+                self.priority(),
+                metadata,
+            ).with_active(false));
+        }
+
+        Ok(sessions)
+    }
+
+    async fn resolve(&self, session_name: &str, _context: &SessionContext) -> Result<SessionMetadata> {
+        let repo = self
+            .find_repo(session_name)
+            .await
+            .ok_or_else(|| anyhow!("Repository not found: {}", session_name))?;
+
+        Ok(SessionMetadata::new("repo".to_string())
+            .with_exists(false)
+            .with_path(repo.path.to_string_lossy().to_string()))
+    }
+
+    async fn switch(&self, session_name: &str, metadata: &SessionMetadata) -> Result<()> {
+        let tmux_session_name = self.ensure_session(session_name, metadata).await?;
+        self.tmux.switch_or_attach(&tmux_session_name).await
+    }
+
+    async fn start(&self, session_name: &str, metadata: &SessionMetadata) -> Result<()> {
+        self.ensure_session(session_name, metadata).await.map(|_| ())
+    }
+
+    async fn preview(&self, session_name: &str, metadata: &SessionMetadata) -> Result<String> {
+        let mut preview = format!("\x1b[0;36m○ Repository: {}\x1b[0m\n\n", session_name);
+
+        if let Some(path) = &metadata.path {
+            preview.push_str(&format!("\x1b[1;33mPath:\x1b[0m {}\n", path));
+            if let Some(branch) = Self::current_branch(path).await {
+                preview.push_str(&format!("\x1b[1;33mBranch:\x1b[0m {}\n", branch));
+            }
+        }
+
+        preview.push_str("\n\x1b[1;33mSession will be created when selected\x1b[0m\n");
+        Ok(preview)
+    }
+
+    fn get_help_text(&self) -> Vec<String> {
+        vec![
+            "\x1b[0;36m○\x1b[0m - Git repository (no session yet)".to_string(),
+        ]
+    }
+}
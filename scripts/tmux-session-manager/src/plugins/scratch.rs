@@ -8,6 +8,12 @@ pub struct ScratchPlugin {
     tmux: TmuxClient,
 }
 
+impl Default for ScratchPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ScratchPlugin {
     pub fn new() -> Self {
         Self {
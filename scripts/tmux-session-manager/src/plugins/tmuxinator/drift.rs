@@ -0,0 +1,202 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+use crate::core::directories;
+use crate::core::session::TmuxPane;
+use crate::core::tmux::TmuxClient;
+use super::launcher;
+use super::project::{Project, Window};
+
+/// A way a running session differs from its project file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Drift {
+    /// The project defines the window but the session has none by that name
+    MissingWindow(String),
+    /// The session has a window the project does not define
+    ExtraWindow(String),
+    PaneCount { window: String, expected: usize, actual: usize },
+    /// None of the window's panes is inside the directory the window starts in
+    Root { window: String, expected: PathBuf, actual: String },
+}
+
+impl Drift {
+    pub fn describe(&self) -> String {
+        match self {
+            Drift::MissingWindow(window) => format!("Window '{}' is missing", window),
+            Drift::ExtraWindow(window) => format!("Window '{}' is not in the project", window),
+            Drift::PaneCount { window, expected, actual } => {
+                format!("Window '{}' has {} pane(s), the project defines {}", window, actual, expected)
+            }
+            Drift::Root { window, expected, actual } => format!(
+                "Window '{}' is in {}, not {}",
+                window,
+                directories::abbreviate_home(Path::new(actual)),
+                directories::abbreviate_home(expected)
+            ),
+        }
+    }
+}
+
+/// A window of the running session with its panes, in session order
+struct LiveWindow<'a> {
+    name: &'a str,
+    panes: Vec<&'a TmuxPane>,
+}
+
+fn live_windows(panes: &[TmuxPane]) -> Vec<LiveWindow<'_>> {
+    let mut windows: Vec<(u32, LiveWindow)> = Vec::new();
+    for pane in panes {
+        match windows.iter_mut().find(|(index, _)| *index == pane.window_index) {
+            Some((_, window)) => window.panes.push(pane),
+            None => windows.push((pane.window_index, LiveWindow { name: &pane.window_name, panes: vec![pane] })),
+        }
+    }
+    windows.into_iter().map(|(_, window)| window).collect()
+}
+
+/// Compare the session's panes (from `TmuxClient::list_panes`) against the project. Windows
+/// are matched by name; a window still counts as in its root when a pane has `cd`ed deeper.
+pub fn compare(project: &Project, panes: &[TmuxPane]) -> Vec<Drift> {
+    let live = live_windows(panes);
+    let mut drift = Vec::new();
+
+    for window in &project.windows {
+        let Some(running) = live.iter().find(|running| running.name == window.name) else {
+            drift.push(Drift::MissingWindow(window.name.clone()));
+            continue;
+        };
+
+        // A window written without panes still opens with one
+        let expected = window.panes.len().max(1);
+        if running.panes.len() != expected {
+            drift.push(Drift::PaneCount { window: window.name.clone(), expected, actual: running.panes.len() });
+        }
+
+        if let Some(dir) = project.window_dir(window) {
+            let dir = dir.canonicalize().unwrap_or(dir);
+            if !running.panes.iter().any(|pane| Path::new(&pane.current_path).starts_with(&dir)) {
+                drift.push(Drift::Root {
+                    window: window.name.clone(),
+                    expected: dir,
+                    actual: running.panes[0].current_path.clone(),
+                });
+            }
+        }
+    }
+
+    for running in &live {
+        if !project.windows.iter().any(|window| window.name == running.name) {
+            drift.push(Drift::ExtraWindow(running.name.to_string()));
+        }
+    }
+
+    drift
+}
+
+/// The project's windows the session has no window for
+pub fn missing_windows<'a>(project: &'a Project, panes: &[TmuxPane]) -> Vec<&'a Window> {
+    project
+        .windows
+        .iter()
+        .filter(|window| !panes.iter().any(|pane| pane.window_name == window.name))
+        .collect()
+}
+
+/// Add the missing windows to a running session, built as the launcher would build them,
+/// and return their names. Everything else that drifted is left alone.
+pub async fn reconcile(tmux: &TmuxClient, session_name: &str, project: &Project) -> Result<Vec<String>> {
+    let panes = tmux.list_panes(session_name).await?;
+
+    let mut added = Vec::new();
+    for window in missing_windows(project, &panes) {
+        launcher::add_window(tmux, session_name, project, window).await?;
+        added.push(window.name.clone());
+    }
+    Ok(added)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pane(window_index: u32, window_name: &str, path: &Path) -> TmuxPane {
+        TmuxPane {
+            window_index,
+            window_name: window_name.to_string(),
+            window_layout: String::new(),
+            window_active: false,
+            pane_index: 0,
+            pane_active: false,
+            current_command: "bash".to_string(),
+            current_path: path.display().to_string(),
+        }
+    }
+
+    /// A project rooted in a temporary directory with `web` and `api` windows in their own roots
+    fn project(root: &Path) -> Project {
+        std::fs::create_dir_all(root.join("web/src")).unwrap();
+        std::fs::create_dir_all(root.join("api")).unwrap();
+        Project::parse(&format!(
+            "name: app\nroot: {}\nwindows:\n  - web:\n      root: web\n      panes: [npm start, npm test]\n  - api:\n      root: api\n  - logs: tail -f log\n",
+            root.display()
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn reports_missing_and_extra_windows() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().canonicalize().unwrap();
+        let project = project(&root);
+
+        let panes = vec![
+            pane(1, "web", &root.join("web")),
+            pane(1, "web", &root.join("web")),
+            pane(2, "api", &root.join("api")),
+            pane(3, "scratch", &root),
+        ];
+
+        assert_eq!(compare(&project, &panes), vec![
+            Drift::MissingWindow("logs".to_string()),
+            Drift::ExtraWindow("scratch".to_string()),
+        ]);
+        let missing: Vec<&str> = missing_windows(&project, &panes).iter().map(|w| w.name.as_str()).collect();
+        assert_eq!(missing, vec!["logs"]);
+    }
+
+    #[test]
+    fn reports_pane_counts_that_differ() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().canonicalize().unwrap();
+        let project = project(&root);
+
+        let panes = vec![
+            pane(1, "web", &root.join("web")),
+            pane(2, "api", &root.join("api")),
+            pane(3, "logs", &root),
+        ];
+
+        assert_eq!(compare(&project, &panes), vec![
+            Drift::PaneCount { window: "web".to_string(), expected: 2, actual: 1 },
+        ]);
+    }
+
+    #[test]
+    fn reports_windows_that_left_their_root() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().canonicalize().unwrap();
+        let project = project(&root);
+
+        // Moving deeper into the root is fine; leaving it is drift
+        let panes = vec![
+            pane(1, "web", &root.join("web/src")),
+            pane(1, "web", &root.join("web")),
+            pane(2, "api", Path::new("/tmp")),
+            pane(3, "logs", &root),
+        ];
+
+        assert_eq!(compare(&project, &panes), vec![
+            Drift::Root { window: "api".to_string(), expected: root.join("api"), actual: "/tmp".to_string() },
+        ]);
+    }
+}
@@ -0,0 +1,252 @@
+use anyhow::{anyhow, Result};
+use std::path::Path;
+use tokio::process::Command;
+
+use crate::core::tmux::TmuxClient;
+use super::project::{Pane, Project, Window};
+
+/// Build a project's session through tmux directly, the way `tmuxinator start` would:
+/// `on_project_start`, then on first start `on_project_first_start` and every window with its
+/// panes, commands and layout. The session is left detached, or killed if it could not be
+/// built completely.
+pub async fn launch(tmux: &TmuxClient, session_name: &str, project: &Project) -> Result<()> {
+    if project.uses_erb {
+        return Err(anyhow!("Tmuxinator project '{}' uses ERB, which needs the tmuxinator gem", session_name));
+    }
+
+    if project.windows.is_empty() {
+        return Err(anyhow!("Tmuxinator project '{}' defines no windows", session_name));
+    }
+
+    let root = project.root_dir();
+    if let Some(root) = &root {
+        if !root.is_dir() {
+            return Err(anyhow!("Project root does not exist: {}", root.display()));
+        }
+    }
+
+    run_hook(&project.hooks.on_project_start, root.as_deref()).await?;
+
+    if tmux.has_session(session_name).await {
+        return run_hook(&project.hooks.on_project_restart, root.as_deref()).await;
+    }
+    run_hook(&project.hooks.on_project_first_start, root.as_deref()).await?;
+
+    // A half-built session would be taken for the project and never rebuilt
+    if let Err(e) = build_session(tmux, session_name, project).await {
+        let _ = tmux.kill_session(session_name).await;
+        return Err(e);
+    }
+
+    Ok(())
+}
+
+/// Create the session with every window, then select the startup window and pane
+async fn build_session(tmux: &TmuxClient, session_name: &str, project: &Project) -> Result<()> {
+    let mut window_ids = Vec::new();
+    for (index, window) in project.windows.iter().enumerate() {
+        let window_id = if index == 0 {
+            let dir = project.window_dir(window).map(|dir| dir.to_string_lossy().to_string());
+            tmux.new_session(session_name, dir.as_deref()).await?;
+            let window_id = tmux.first_window_id(session_name).await?;
+            tmux.rename_window(&window_id, &window.name).await?;
+            build_panes(tmux, project, window, &window_id, dir.as_deref()).await?;
+            window_id
+        } else {
+            add_window(tmux, session_name, project, window).await?
+        };
+        window_ids.push(window_id);
+    }
+
+    let Some(first_window) = window_ids.first() else {
+        return Ok(());
+    };
+
+    let startup_window = project
+        .startup_window
+        .as_ref()
+        .and_then(|startup| startup_window_index(project, startup))
+        .and_then(|index| window_ids.get(index))
+        .unwrap_or(first_window);
+    tmux.select_window(startup_window).await?;
+
+    if let Some(pane) = project.startup_pane {
+        // Pane numbers follow tmux's pane-base-index, as they do in tmuxinator
+        let _ = tmux.select_pane(&format!("{}.{}", startup_window, pane)).await;
+    }
+
+    Ok(())
+}
+
+/// Append one of the project's windows to a running session, panes and commands included,
+/// and return its id
+pub async fn add_window(tmux: &TmuxClient, session_name: &str, project: &Project, window: &Window) -> Result<String> {
+    let dir = project.window_dir(window).map(|dir| dir.to_string_lossy().to_string());
+    let window_id = tmux.new_window(session_name, &window.name, dir.as_deref(), None).await?;
+    build_panes(tmux, project, window, &window_id, dir.as_deref()).await?;
+    Ok(window_id)
+}
+
+/// Split a freshly created window into the project's panes and type each pane's commands
+async fn build_panes(tmux: &TmuxClient, project: &Project, window: &Window, window_id: &str, dir: Option<&str>) -> Result<()> {
+    let Some((first_pane, other_panes)) = window.panes.split_first() else {
+        return send_commands(tmux, window_id, &project.pre_window).await;
+    };
+
+    // With `-d` the first pane stays active, so the window id still addresses it
+    send_commands(tmux, window_id, &pane_commands(project, window, first_pane)).await?;
+
+    for pane in other_panes {
+        let pane_id = tmux.split_window(window_id, dir, None).await?;
+        // Tiling between splits keeps room for the next pane in small windows
+        let _ = tmux.select_layout(window_id, "tiled").await;
+        send_commands(tmux, &pane_id, &pane_commands(project, window, pane)).await?;
+    }
+
+    // Layouts can fail to apply when the client size differs; the panes are still there
+    if let Some(layout) = &window.layout {
+        let _ = tmux.select_layout(window_id, layout).await;
+    }
+
+    Ok(())
+}
+
+/// Everything typed into a pane: `pre_window`, the window's `pre`, then the pane's own commands
+fn pane_commands(project: &Project, window: &Window, pane: &Pane) -> Vec<String> {
+    project.pre_window
+        .iter()
+        .chain(&window.pre)
+        .chain(&pane.commands)
+        .cloned()
+        .collect()
+}
+
+async fn send_commands(tmux: &TmuxClient, target: &str, commands: &[String]) -> Result<()> {
+    for command in commands {
+        tmux.send_keys(target, command).await?;
+    }
+    Ok(())
+}
+
+/// Position of the `startup_window`, given by name or by number as tmuxinator accepts both
+fn startup_window_index(project: &Project, startup: &str) -> Option<usize> {
+    project
+        .windows
+        .iter()
+        .position(|window| window.name == startup)
+        .or_else(|| startup.parse::<usize>().ok().map(|number| number.saturating_sub(1)))
+}
+
+/// Run a hook's commands in one shell from the project root. Like tmuxinator's generated
+/// script, a failing hook does not stop the project from starting.
+async fn run_hook(commands: &[String], root: Option<&Path>) -> Result<()> {
+    if commands.is_empty() {
+        return Ok(());
+    }
+
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(commands.join("\n"));
+    if let Some(root) = root {
+        command.current_dir(root);
+    }
+
+    command.output().await?;
+    Ok(())
+}
+
+/// Whether the tmuxinator gem can be run
+pub async fn is_gem_available() -> bool {
+    Command::new("tmuxinator")
+        .arg("version")
+        .output()
+        .await
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// Start a project from its file through the tmuxinator gem, without attaching. The name is
+/// passed along because the gem refuses files without `name:`, which the picker names itself.
+pub async fn launch_with_gem(config_path: &str, session_name: &str) -> Result<()> {
+    let output = Command::new("tmuxinator")
+        .args(["start", "--no-attach", "-n", session_name, "-p", config_path])
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        return Err(anyhow!("Failed to start tmuxinator session: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command as StdCommand;
+
+    /// A tmux server of its own on a socket in a temporary directory, killed when dropped
+    struct TestServer {
+        socket: String,
+        _dir: tempfile::TempDir,
+    }
+
+    impl TestServer {
+        /// None when tmux is not installed
+        fn start() -> Option<Self> {
+            let dir = tempfile::tempdir().ok()?;
+            let socket = dir.path().join("tmux.sock").to_string_lossy().to_string();
+            let started = StdCommand::new("tmux")
+                .args(["-S", &socket, "-f", "/dev/null", "new-session", "-d", "-s", "base"])
+                .status()
+                .is_ok_and(|status| status.success());
+            started.then_some(Self { socket, _dir: dir })
+        }
+
+        fn client(&self) -> TmuxClient {
+            TmuxClient::new().with_socket(&self.socket)
+        }
+
+        fn set_default_size(&self, size: &str) {
+            StdCommand::new("tmux")
+                .args(["-S", &self.socket, "set-option", "-g", "default-size", size])
+                .status()
+                .unwrap();
+        }
+    }
+
+    impl Drop for TestServer {
+        fn drop(&mut self) {
+            let _ = StdCommand::new("tmux").args(["-S", &self.socket, "kill-server"]).status();
+        }
+    }
+
+    fn project(root: &Path, windows: &str) -> Project {
+        Project::parse(&format!("name: app\nroot: {}\nstartup_window: logs\nwindows:\n{}", root.display(), windows)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn launch_builds_windows_and_panes() {
+        let Some(server) = TestServer::start() else { return };
+        let tmux = server.client();
+        let root = tempfile::tempdir().unwrap();
+        let project = project(root.path(), "  - editor:\n      panes: [vim, make watch]\n  - logs: tail -f log\n");
+
+        launch(&tmux, "app", &project).await.unwrap();
+
+        let panes = tmux.list_panes("app").await.unwrap();
+        let windows: Vec<(&str, bool)> = panes.iter().map(|pane| (pane.window_name.as_str(), pane.window_active)).collect();
+        assert_eq!(windows, vec![("editor", false), ("editor", false), ("logs", true)]);
+    }
+
+    #[tokio::test]
+    async fn failed_launch_kills_the_partly_built_session() {
+        let Some(server) = TestServer::start() else { return };
+        let tmux = server.client();
+        let root = tempfile::tempdir().unwrap();
+        // Too small a window for more than two panes, so the third split fails
+        server.set_default_size("10x4");
+        let project = project(root.path(), "  - logs: tail -f log\n  - editor:\n      panes: [vim, make watch, git status]\n");
+
+        assert!(launch(&tmux, "app", &project).await.is_err());
+        assert!(!tmux.has_session("app").await);
+    }
+}
@@ -0,0 +1,403 @@
+pub mod drift;
+pub mod launcher;
+pub mod project;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+
+use crate::core::directories;
+use crate::core::{session::{SessionContext, SessionItem, SessionMetadata, SessionStamp}, tmux::TmuxClient};
+use crate::plugins::SessionPlugin;
+
+use project::{Project, LOCAL_PROJECT_FILES};
+
+pub struct TmuxinatorPlugin {
+    tmux: TmuxClient,
+    config_dirs: Vec<String>,
+    /// Directories checked for a project-local `.tmuxinator.yml`, besides running sessions' ones
+    project_dirs: Vec<PathBuf>,
+}
+
+/// A project file and the name its session goes by
+struct ProjectFile {
+    name: String,
+    path: String,
+}
+
+impl Default for TmuxinatorPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TmuxinatorPlugin {
+    pub fn new() -> Self {
+        let mut config_dirs = Vec::new();
+
+        // Default tmuxinator config locations
+        if let Some(home) = dirs::home_dir() {
+            config_dirs.push(format!("{}/.config/tmuxinator", home.display()));
+            config_dirs.push(format!("{}/.tmuxinator", home.display()));
+        }
+
+        // Check XDG_CONFIG_HOME
+        if let Ok(xdg_config) = std::env::var("XDG_CONFIG_HOME") {
+            config_dirs.push(format!("{}/tmuxinator", xdg_config));
+        }
+
+        Self {
+            tmux: TmuxClient::new(),
+            config_dirs,
+            project_dirs: Vec::new(),
+        }
+    }
+
+    pub fn with_project_dirs(mut self, dirs: &[String]) -> Self {
+        self.project_dirs = dirs.iter().map(|dir| directories::expand_home(dir)).collect();
+        self
+    }
+
+    /// Every project file: those in the tmuxinator config directories, then project-local
+    /// ones in the configured directories and those running sessions are open in
+    async fn find_project_files(&self, context: &SessionContext) -> Result<Vec<ProjectFile>> {
+        let mut paths = Vec::new();
+
+        for config_dir in &self.config_dirs {
+            let config_path = Path::new(config_dir);
+            if !config_path.exists() {
+                continue;
+            }
+
+            let mut entries = tokio::fs::read_dir(config_path).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                if path.extension().is_some_and(|extension| extension == "yml" || extension == "yaml") {
+                    paths.push(path);
+                }
+            }
+        }
+
+        let session_dirs = context
+            .all_tmux_sessions
+            .iter()
+            .filter_map(|session| session.options.path.as_ref().or(session.current_path.as_ref()))
+            .map(PathBuf::from);
+        for dir in self.project_dirs.iter().cloned().chain(session_dirs) {
+            if let Some(path) = Self::local_project_file(&dir) {
+                if !paths.contains(&path) {
+                    paths.push(path);
+                }
+            }
+        }
+
+        let mut files: Vec<ProjectFile> = Vec::new();
+        for path in paths {
+            let name = Self::project_name(&path).await;
+            // Like tmuxinator, the first project claiming a name is the one started under it
+            if name.is_empty() || files.iter().any(|file| file.name == name) {
+                continue;
+            }
+            files.push(ProjectFile { name, path: path.to_string_lossy().to_string() });
+        }
+
+        Ok(files)
+    }
+
+    /// The project-local file of `dir`, or of a parent directory within the same repository
+    fn local_project_file(dir: &Path) -> Option<PathBuf> {
+        let home = dirs::home_dir();
+
+        for ancestor in dir.ancestors() {
+            if let Some(file) = LOCAL_PROJECT_FILES.iter().map(|name| ancestor.join(name)).find(|file| file.is_file()) {
+                return Some(file);
+            }
+            if ancestor.join(".git").exists() || home.as_deref() == Some(ancestor) {
+                break;
+            }
+        }
+        None
+    }
+
+    /// The session name tmuxinator uses for a project: its `name:`, else the file name, or
+    /// for a project-local file the directory holding it
+    async fn project_name(path: &Path) -> String {
+        if let Some(name) = Project::load(path).await.ok().and_then(|project| project.name) {
+            return name;
+        }
+
+        let named_after = if project::is_local_file(path) { path.parent().and_then(Path::file_name) } else { path.file_stem() };
+        named_after.map(|name| name.to_string_lossy().to_string()).unwrap_or_default()
+    }
+
+    /// Record that the project's session was started through this plugin
+    async fn stamp_session(&self, session_name: &str) -> Result<()> {
+        if self.tmux.has_session(session_name).await {
+            let stamp = SessionStamp::new(self.name()).with_project(session_name);
+            self.tmux.stamp_session(session_name, &stamp).await?;
+        }
+        Ok(())
+    }
+
+    /// Start a project's session detached, natively unless it relies on what only the gem
+    /// implements, or cannot be parsed here, and the gem is installed
+    async fn launch_project(&self, session_name: &str, metadata: &SessionMetadata) -> Result<()> {
+        let config_path = metadata.get_property("config_path")
+            .ok_or_else(|| anyhow!("No tmuxinator config found for session: {}", session_name))?;
+
+        match Project::load(Path::new(config_path)).await {
+            Ok(project) if project.needs_gem() && launcher::is_gem_available().await => {
+                launcher::launch_with_gem(config_path, session_name).await?
+            }
+            Ok(project) => launcher::launch(&self.tmux, session_name, &project).await?,
+            // The gem reads some files this parser rejects, and reports its own errors otherwise
+            Err(_) if launcher::is_gem_available().await => launcher::launch_with_gem(config_path, session_name).await?,
+            Err(e) => return Err(anyhow!("Tmuxinator project '{}' cannot be loaded: {}", session_name, e)),
+        }
+
+        self.stamp_session(session_name).await
+    }
+
+    /// The project behind a running session, as long as its windows can be known without the gem
+    async fn running_project(&self, session_name: &str, metadata: &SessionMetadata) -> Result<Project> {
+        if !self.tmux.has_session(session_name).await {
+            return Err(anyhow!("'{}' is not running", session_name));
+        }
+
+        let config_path = metadata.get_property("config_path")
+            .ok_or_else(|| anyhow!("No project file for '{}'", session_name))?;
+        let project = Project::load(Path::new(config_path)).await?;
+        if project.uses_erb {
+            return Err(anyhow!("'{}' uses ERB, so its windows are only known to the tmuxinator gem", session_name));
+        }
+
+        Ok(project)
+    }
+
+    /// The project as tmuxinator would build it: root, hooks, and every window with its panes
+    fn describe_project(project: &Project) -> String {
+        let mut summary = String::new();
+
+        if let Some(name) = &project.name {
+            summary.push_str(&format!("Name: {}\n", name));
+        }
+        if let Some(root) = &project.root {
+            summary.push_str(&format!("Root: {}\n", root));
+        }
+        if !project.pre_window.is_empty() {
+            summary.push_str(&format!("Pre-window: {}\n", project.pre_window.join("; ")));
+        }
+        for (hook, commands) in project.hooks.configured() {
+            summary.push_str(&format!("{}: {}\n", hook, commands.join("; ")));
+        }
+
+        if !project.windows.is_empty() {
+            summary.push_str("\nWindows:\n");
+        }
+        for (index, window) in project.windows.iter().enumerate() {
+            let mut details = vec![format!("{} pane{}", window.panes.len(), if window.panes.len() == 1 { "" } else { "s" })];
+            if let Some(layout) = &window.layout {
+                details.push(layout.clone());
+            }
+            if let Some(root) = &window.root {
+                details.push(format!("in {}", root));
+            }
+            summary.push_str(&format!("{}: {} ({})\n", index + 1, window.name, details.join(", ")));
+
+            for pane in &window.panes {
+                let commands = if pane.commands.is_empty() { "(shell)".to_string() } else { pane.commands.join("; ") };
+                match &pane.title {
+                    Some(title) => summary.push_str(&format!("   - {}: {}\n", title, commands)),
+                    None => summary.push_str(&format!("   - {}\n", commands)),
+                }
+            }
+        }
+
+        summary
+    }
+}
+
+#[async_trait]
+impl SessionPlugin for TmuxinatorPlugin {
+    fn name(&self) -> &str {
+        "tmuxinator"
+    }
+
+    fn description(&self) -> &str {
+        "Tmuxinator configuration sessions"
+    }
+
+    fn priority(&self) -> u32 {
+        50
+    }
+
+    async fn discover(&self, context: &SessionContext) -> Result<Vec<SessionItem>> {
+        let files = self.find_project_files(context).await?;
+        let mut sessions = Vec::new();
+
+        for ProjectFile { name: config_name, path: config_path } in files {
+            // Check if a session with this name already exists
+            let is_active = context
+                .all_tmux_sessions
+                .iter()
+                .any(|s| s.name == config_name);
+
+            let is_current = context.current_session.as_ref() == Some(&config_name);
+
+            let metadata = SessionMetadata::new("tmuxinator".to_string())
+                .with_exists(is_active)
+                .with_path(config_path.clone())
+                .with_property("config_path".to_string(), config_path);
+
+            let mut session_item = SessionItem::new(
+                config_name,
+                "tmuxinator".to_string(),
+                self.priority(),
+                metadata,
+            ).with_current(is_current)
+              .with_active(is_active);
+
+            // If session is active, get its timestamp
+            if is_active {
+                if let Some(tmux_session) = context.all_tmux_sessions
+                    .iter()
+                    .find(|s| s.name == session_item.name) {
+                    session_item = session_item.with_timestamp(tmux_session.last_attached);
+                }
+            }
+
+            sessions.push(session_item);
+        }
+
+        Ok(sessions)
+    }
+
+    async fn resolve(&self, session_name: &str, context: &SessionContext) -> Result<SessionMetadata> {
+        let files = self.find_project_files(context).await?;
+
+        if let Some(file) = files.into_iter().find(|file| file.name == session_name) {
+            let is_active = self.tmux.has_session(session_name).await;
+
+            return Ok(SessionMetadata::new("tmuxinator".to_string())
+                .with_exists(is_active)
+                .with_path(file.path.clone())
+                .with_property("config_path".to_string(), file.path));
+        }
+
+        Err(anyhow!("Tmuxinator config not found for session: {}", session_name))
+    }
+
+    async fn switch(&self, session_name: &str, metadata: &SessionMetadata) -> Result<()> {
+        if !self.tmux.has_session(session_name).await {
+            self.launch_project(session_name, metadata).await?;
+        }
+        self.tmux.switch_or_attach(session_name).await
+    }
+
+    async fn start(&self, session_name: &str, metadata: &SessionMetadata) -> Result<()> {
+        self.launch_project(session_name, metadata).await
+    }
+
+    async fn rename(&self, old_name: &str, new_name: &str, _metadata: &SessionMetadata) -> Result<Option<String>> {
+        self.tmux.rename_session(old_name, new_name).await?;
+
+        // The project file still names the session, so starting it again will not find this one
+        Ok(Some(format!(
+            "Renamed '{}' to '{}', but tmuxinator project '{}' still uses the old name; starting it will create a second session",
+            old_name, new_name, old_name
+        )))
+    }
+
+    async fn preview(&self, session_name: &str, metadata: &SessionMetadata) -> Result<String> {
+        let mut preview = format!("\x1b[0;37m● Tmuxinator: {}\x1b[0m\n\n", session_name);
+
+        if let Some(config_path) = metadata.get_property("config_path") {
+            preview.push_str(&format!("\x1b[1;33mConfig file:\x1b[0m\n{}\n\n", config_path));
+
+            let project = Project::load(Path::new(config_path)).await;
+            match &project {
+                Ok(project) => {
+                    preview.push_str("\x1b[1;33mConfiguration:\x1b[0m\n");
+                    for line in Self::describe_project(project).lines() {
+                        preview.push_str(&format!("  {}\n", line));
+                    }
+                    preview.push('\n');
+
+                    if project.uses_erb {
+                        preview.push_str("\x1b[0;36mUses ERB: values are shown before tmuxinator renders them\x1b[0m\n\n");
+                    }
+                    if !project.gem_only.is_empty() {
+                        preview.push_str(&format!(
+                            "\x1b[0;36mStarts through the tmuxinator gem for: {}\x1b[0m\n\n",
+                            project.gem_only.join(", ")
+                        ));
+                    }
+                    if !project.issues.is_empty() {
+                        preview.push_str("\x1b[0;31mProblems:\x1b[0m\n");
+                        for issue in &project.issues {
+                            preview.push_str(&format!("\x1b[0;31m  {}\x1b[0m\n", issue));
+                        }
+                        preview.push('\n');
+                    }
+                }
+                Err(e) => {
+                    preview.push_str(&format!("\x1b[0;31mCould not read configuration: {}\x1b[0m\n\n", e));
+                }
+            }
+
+            if metadata.exists {
+                preview.push_str("\x1b[1;32mSession is currently active\x1b[0m\n");
+
+                // Try to show session info
+                if let Ok((windows, _)) = self.tmux.get_session_info(session_name).await {
+                    preview.push_str(&format!("Windows: {}\n", windows));
+                }
+
+                // Unrendered ERB would make every window look different
+                if let Some(project) = project.as_ref().ok().filter(|project| !project.uses_erb) {
+                    if let Ok(panes) = self.tmux.list_panes(session_name).await {
+                        let drift = drift::compare(project, &panes);
+                        if drift.is_empty() {
+                            preview.push_str("\x1b[0;32mSession matches the project file\x1b[0m\n");
+                        } else {
+                            preview.push_str("\n\x1b[1;33mDiffers from the project file:\x1b[0m\n");
+                            for difference in &drift {
+                                preview.push_str(&format!("\x1b[0;33m  {}\x1b[0m\n", difference.describe()));
+                            }
+                            if drift.iter().any(|difference| matches!(difference, drift::Drift::MissingWindow(_))) {
+                                preview.push_str("Alt-r adds the missing windows\n");
+                            }
+                        }
+                    }
+                }
+            } else {
+                preview.push_str("\x1b[1;33mSession will be started from configuration\x1b[0m\n");
+            }
+        } else {
+            preview.push_str("Configuration file not found\n");
+        }
+
+        Ok(preview)
+    }
+
+    async fn missing_windows(&self, session_name: &str, metadata: &SessionMetadata) -> Result<Vec<String>> {
+        let project = self.running_project(session_name, metadata).await?;
+        let panes = self.tmux.list_panes(session_name).await?;
+
+        Ok(drift::missing_windows(&project, &panes)
+            .iter()
+            .map(|window| window.name.clone())
+            .collect())
+    }
+
+    async fn add_missing_windows(&self, session_name: &str, metadata: &SessionMetadata) -> Result<Vec<String>> {
+        let project = self.running_project(session_name, metadata).await?;
+        drift::reconcile(&self.tmux, session_name, &project).await
+    }
+
+    fn get_help_text(&self) -> Vec<String> {
+        vec![
+            "\x1b[0;90m●\x1b[0m - Tmuxinator config".to_string(),
+        ]
+    }
+}
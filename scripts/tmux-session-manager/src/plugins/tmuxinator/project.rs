@@ -0,0 +1,453 @@
+use anyhow::{anyhow, Result};
+use serde_yaml::{Mapping, Value};
+use std::path::{Path, PathBuf};
+
+use crate::core::directories;
+
+/// Names tmuxinator looks for in a project directory
+pub const LOCAL_PROJECT_FILES: &[&str] = &[".tmuxinator.yml", ".tmuxinator.yaml"];
+
+/// Layouts tmux knows by name; anything else must be a layout string such as `bb62,159x48,0,0{...}`
+const NAMED_LAYOUTS: &[&str] = &[
+    "even-horizontal",
+    "even-vertical",
+    "main-horizontal",
+    "main-horizontal-mirrored",
+    "main-vertical",
+    "main-vertical-mirrored",
+    "tiled",
+];
+
+/// Settings the native launcher does not implement; projects using them start through the gem
+const GEM_ONLY_SETTINGS: &[&str] = &[
+    "tmux_command",
+    "tmux_options",
+    "socket_name",
+    "socket_path",
+    "enable_pane_titles",
+    "pane_title_format",
+    "pane_title_position",
+    "on_project_exit",
+];
+
+/// A tmuxinator project file, parsed into what tmuxinator would build from it
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Project {
+    pub name: Option<String>,
+    pub root: Option<String>,
+    /// For a project-local file, the directory holding it: relative roots start there, and
+    /// it is the root when none is set
+    pub dir: Option<PathBuf>,
+    pub windows: Vec<Window>,
+    /// Commands run in every pane before its own
+    pub pre_window: Vec<String>,
+    pub hooks: Hooks,
+    pub startup_window: Option<String>,
+    pub startup_pane: Option<u32>,
+    /// The file contains ERB (`<% %>`), which tmuxinator renders before reading the YAML;
+    /// values shown here are unrendered
+    pub uses_erb: bool,
+    /// Problems tmuxinator would trip over, or keys it no longer supports
+    pub issues: Vec<String>,
+    /// Settings in use that only the tmuxinator gem implements
+    pub gem_only: Vec<String>,
+}
+
+/// The `on_project_*` hooks
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Hooks {
+    pub on_project_start: Vec<String>,
+    pub on_project_first_start: Vec<String>,
+    pub on_project_restart: Vec<String>,
+    pub on_project_exit: Vec<String>,
+    pub on_project_stop: Vec<String>,
+}
+
+impl Hooks {
+    /// Hooks that are set, as `(key, commands)`
+    pub fn configured(&self) -> Vec<(&'static str, &[String])> {
+        [
+            ("on_project_start", &self.on_project_start),
+            ("on_project_first_start", &self.on_project_first_start),
+            ("on_project_restart", &self.on_project_restart),
+            ("on_project_exit", &self.on_project_exit),
+            ("on_project_stop", &self.on_project_stop),
+        ]
+        .into_iter()
+        .filter(|(_, commands)| !commands.is_empty())
+        .map(|(key, commands)| (key, commands.as_slice()))
+        .collect()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Window {
+    pub name: String,
+    pub root: Option<String>,
+    pub layout: Option<String>,
+    /// Commands run in each pane of this window before its own
+    pub pre: Vec<String>,
+    pub panes: Vec<Pane>,
+    /// `synchronize` is set, mirroring input across the window's panes
+    pub synchronize: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Pane {
+    /// Set when the pane is written as `- title: [commands]`
+    pub title: Option<String>,
+    pub commands: Vec<String>,
+}
+
+impl Project {
+    pub async fn load(path: &Path) -> Result<Self> {
+        let content = tokio::fs::read_to_string(path).await?;
+        // `tmuxinator local` runs from the directory of the file; other projects from wherever
+        // tmuxinator was started, which for the picker means their roots should be absolute
+        let dir = if is_local_file(path) { path.parent().map(Path::to_path_buf) } else { None };
+        Self::parse_in(&content, dir)
+    }
+
+    /// Parse a project file. Files using ERB may not be valid YAML until rendered, so for
+    /// them a parse failure yields an empty project flagged `uses_erb` rather than an error.
+    pub fn parse(content: &str) -> Result<Self> {
+        Self::parse_in(content, None)
+    }
+
+    fn parse_in(content: &str, dir: Option<PathBuf>) -> Result<Self> {
+        let uses_erb = content.contains("<%");
+
+        let value: Value = match serde_yaml::from_str(content) {
+            Ok(value) => value,
+            Err(_) if uses_erb => {
+                return Ok(Project {
+                    dir,
+                    uses_erb,
+                    issues: vec!["Uses ERB that must be rendered before the YAML can be read".to_string()],
+                    ..Default::default()
+                })
+            }
+            Err(e) => return Err(anyhow!("Invalid YAML: {}", e)),
+        };
+        let Value::Mapping(map) = value else {
+            return Err(anyhow!("Project file must be a mapping of settings"));
+        };
+
+        let mut project = Project { dir, uses_erb, ..Default::default() };
+
+        project.name = scalar(map.get("name")).or_else(|| project.deprecated(&map, "project_name", "name"));
+        project.root = scalar(map.get("root")).or_else(|| project.deprecated(&map, "project_root", "root"));
+        project.pre_window = commands(map.get("pre_window"));
+        project.startup_window = scalar(map.get("startup_window"));
+        project.startup_pane = scalar(map.get("startup_pane")).and_then(|pane| pane.parse().ok());
+
+        project.hooks = Hooks {
+            on_project_start: commands(map.get("on_project_start")),
+            on_project_first_start: commands(map.get("on_project_first_start")),
+            on_project_restart: commands(map.get("on_project_restart")),
+            on_project_exit: commands(map.get("on_project_exit")),
+            on_project_stop: commands(map.get("on_project_stop")),
+        };
+        if project.hooks.on_project_start.is_empty() {
+            if let Some(pre) = map.get("pre") {
+                project.hooks.on_project_start = commands(Some(pre));
+                project.issues.push("`pre` is deprecated; use `on_project_start`".to_string());
+            }
+        }
+
+        project.gem_only = GEM_ONLY_SETTINGS
+            .iter()
+            .filter(|key| map.contains_key(**key))
+            .map(|key| key.to_string())
+            .collect();
+
+        let windows = match map.get("windows") {
+            Some(windows) => Some(windows),
+            None => map.get("tabs").inspect(|_| project.issues.push("`tabs` is deprecated; use `windows`".to_string())),
+        };
+        match windows {
+            Some(Value::Sequence(windows)) => {
+                for (index, window) in windows.iter().enumerate() {
+                    match parse_window(window) {
+                        Ok(window) => project.windows.push(window),
+                        Err(e) => project.issues.push(format!("Window {}: {}", index + 1, e)),
+                    }
+                }
+            }
+            Some(_) => project.issues.push("`windows` must be a list".to_string()),
+            None => {}
+        }
+
+        if project.windows.iter().any(|window| window.synchronize) {
+            project.gem_only.push("synchronize".to_string());
+        }
+
+        project.validate();
+        Ok(project)
+    }
+
+    /// Whether only the tmuxinator gem can start this project as written
+    pub fn needs_gem(&self) -> bool {
+        self.uses_erb || !self.gem_only.is_empty()
+    }
+
+    /// The project root with `~` expanded, or the directory of a project-local file
+    pub fn root_dir(&self) -> Option<PathBuf> {
+        self.root.as_deref().map(|root| self.resolve(root)).or_else(|| self.dir.clone())
+    }
+
+    /// Where a window starts: its own root, relative to the project root, or the project root
+    pub fn window_dir(&self, window: &Window) -> Option<PathBuf> {
+        match (&window.root, self.root_dir()) {
+            (Some(root), Some(project_root)) => Some(project_root.join(directories::expand_home(root))),
+            (Some(root), None) => Some(self.resolve(root)),
+            (None, project_root) => project_root,
+        }
+    }
+
+    fn resolve(&self, root: &str) -> PathBuf {
+        let path = directories::expand_home(root);
+        match &self.dir {
+            Some(dir) if path.is_relative() => dir.join(path),
+            _ => path,
+        }
+    }
+
+    fn deprecated(&mut self, map: &Mapping, key: &str, replacement: &str) -> Option<String> {
+        let value = scalar(map.get(key))?;
+        self.issues.push(format!("`{}` is deprecated; use `{}`", key, replacement));
+        Some(value)
+    }
+
+    fn validate(&mut self) {
+        if self.windows.is_empty() && !self.uses_erb {
+            self.issues.push("No windows defined".to_string());
+        }
+
+        // ERB commonly computes the root, so an unrendered one can't be checked
+        let roots = std::iter::once(("Root", self.root.clone(), self.root_dir()))
+            .chain(self.windows.iter().map(|window| ("Window root", window.root.clone(), self.window_dir(window))));
+        let missing_roots: Vec<String> = roots
+            .filter_map(|(label, root, dir)| Some((label, root?, dir?)))
+            .filter(|(_, root, dir)| !root.contains("<%") && !dir.is_dir())
+            .map(|(label, root, _)| format!("{} does not exist: {}", label, root))
+            .collect();
+        self.issues.extend(missing_roots);
+
+        for window in &self.windows {
+            if let Some(layout) = &window.layout {
+                if !NAMED_LAYOUTS.contains(&layout.as_str()) && !layout.contains(',') {
+                    self.issues.push(format!("Window '{}' has unknown layout '{}'", window.name, layout));
+                }
+            }
+        }
+
+        if let Some(startup) = &self.startup_window {
+            let known = self.windows.iter().enumerate().any(|(index, window)| {
+                &window.name == startup || startup.parse::<usize>().is_ok_and(|number| number == index || number == index + 1)
+            });
+            if !known {
+                self.issues.push(format!("startup_window '{}' is not one of the windows", startup));
+            }
+        }
+    }
+}
+
+/// Whether `path` is a project-local `.tmuxinator.yml` rather than a file in the config directories
+pub fn is_local_file(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|file_name| LOCAL_PROJECT_FILES.iter().any(|name| file_name == *name))
+}
+
+/// A window entry is a single-key mapping from its name to a command, a list of pane
+/// commands, or a mapping with `root`, `layout`, `pre` and `panes`
+fn parse_window(value: &Value) -> Result<Window> {
+    let Value::Mapping(entry) = value else {
+        return Err(anyhow!("expected `- name: ...`"));
+    };
+    let Some((name, body)) = entry.iter().next() else {
+        return Err(anyhow!("empty window entry"));
+    };
+    let name = scalar(Some(name)).ok_or_else(|| anyhow!("window name must be text"))?;
+    let mut window = Window { name, ..Default::default() };
+
+    match body {
+        Value::Null => window.panes.push(Pane::default()),
+        Value::Mapping(settings) => {
+            window.root = scalar(settings.get("root"));
+            window.layout = scalar(settings.get("layout"));
+            window.pre = commands(settings.get("pre"));
+            window.synchronize = settings.get("synchronize").is_some_and(|value| value != &Value::Bool(false));
+            match settings.get("panes") {
+                Some(Value::Sequence(panes)) => window.panes = panes.iter().map(parse_pane).collect(),
+                Some(Value::Null) | None => window.panes.push(Pane::default()),
+                Some(_) => return Err(anyhow!("`panes` of '{}' must be a list", window.name)),
+            }
+        }
+        // A window with a single pane running these commands
+        other => window.panes.push(Pane { title: None, commands: commands(Some(other)) }),
+    }
+
+    Ok(window)
+}
+
+/// A pane is a command, a list of commands, nothing, or `title: commands`
+fn parse_pane(value: &Value) -> Pane {
+    match value {
+        Value::Mapping(entry) => match entry.iter().next() {
+            Some((title, body)) => Pane { title: scalar(Some(title)), commands: commands(Some(body)) },
+            None => Pane::default(),
+        },
+        other => Pane { title: None, commands: commands(Some(other)) },
+    }
+}
+
+/// Strings, numbers and booleans as text; YAML spells them all as plain scalars
+fn scalar(value: Option<&Value>) -> Option<String> {
+    match value? {
+        Value::String(text) => Some(text.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        Value::Bool(flag) => Some(flag.to_string()),
+        _ => None,
+    }
+}
+
+/// A command setting: a single command or a list of them
+fn commands(value: Option<&Value>) -> Vec<String> {
+    match value {
+        Some(Value::Sequence(items)) => items.iter().filter_map(|item| scalar(Some(item))).collect(),
+        other => scalar(other).into_iter().collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLOG: &str = r#"
+name: blog
+pre_window: rbenv shell 3.2
+on_project_start:
+  - docker compose up -d
+on_project_stop: docker compose down
+startup_window: editor
+windows:
+  - editor:
+      layout: main-vertical
+      panes:
+        - vim
+        - guard
+        - logs:
+            - cd log
+            - tail -f development.log
+  - server: bundle exec rails s
+  - shell:
+"#;
+
+    #[test]
+    fn parses_settings_and_hooks() {
+        let project = Project::parse(BLOG).unwrap();
+
+        assert_eq!(project.name.as_deref(), Some("blog"));
+        assert_eq!(project.pre_window, vec!["rbenv shell 3.2"]);
+        assert_eq!(project.startup_window.as_deref(), Some("editor"));
+        assert_eq!(project.hooks.on_project_start, vec!["docker compose up -d"]);
+        assert_eq!(project.hooks.configured().len(), 2);
+        assert!(!project.uses_erb);
+        assert!(project.issues.is_empty(), "{:?}", project.issues);
+    }
+
+    #[test]
+    fn parses_each_window_form() {
+        let project = Project::parse(BLOG).unwrap();
+
+        let names: Vec<&str> = project.windows.iter().map(|w| w.name.as_str()).collect();
+        assert_eq!(names, vec!["editor", "server", "shell"]);
+
+        let editor = &project.windows[0];
+        assert_eq!(editor.layout.as_deref(), Some("main-vertical"));
+        assert_eq!(editor.panes.len(), 3);
+        assert_eq!(editor.panes[2].title.as_deref(), Some("logs"));
+        assert_eq!(editor.panes[2].commands, vec!["cd log", "tail -f development.log"]);
+
+        assert_eq!(project.windows[1].panes[0].commands, vec!["bundle exec rails s"]);
+        assert_eq!(project.windows[2].panes, vec![Pane::default()]);
+    }
+
+    #[test]
+    fn reports_problems_tmuxinator_would_hit() {
+        let project = Project::parse(
+            "root: /definitely/not/here\nstartup_window: logs\nwindows:\n  - console:\n      layout: diagonal\n",
+        )
+        .unwrap();
+
+        assert_eq!(project.issues, vec![
+            "Root does not exist: /definitely/not/here",
+            "Window 'console' has unknown layout 'diagonal'",
+            "startup_window 'logs' is not one of the windows",
+        ]);
+        assert_eq!(Project::parse("name: empty\n").unwrap().issues, vec!["No windows defined"]);
+    }
+
+    #[test]
+    fn tolerates_erb_but_rejects_invalid_yaml() {
+        let erb = Project::parse("root: <%= ENV['HOME'] %>/src\nwindows:\n  - <%= @args[0] %>: vim\n").unwrap();
+        assert!(erb.uses_erb);
+
+        assert!(Project::parse("windows: [editor: {panes: [vim]\n").is_err());
+        assert!(Project::parse("- just\n- a list\n").is_err());
+    }
+
+    #[test]
+    fn reads_deprecated_keys_and_flags_them() {
+        let project = Project::parse("project_name: old\npre: make deps\ntabs:\n  - main: top\n").unwrap();
+
+        assert_eq!(project.name.as_deref(), Some("old"));
+        assert_eq!(project.hooks.on_project_start, vec!["make deps"]);
+        assert_eq!(project.windows.len(), 1);
+        assert_eq!(project.issues.len(), 3);
+    }
+
+    #[test]
+    fn window_dirs_start_from_the_project_root() {
+        let project = Project::parse("root: /srv/app\nwindows:\n  - web:\n      root: frontend\n  - logs:\n      root: /var/log\n  - shell:\n").unwrap();
+
+        let dirs: Vec<Option<PathBuf>> = project.windows.iter().map(|w| project.window_dir(w)).collect();
+        assert_eq!(dirs, vec![
+            Some(PathBuf::from("/srv/app/frontend")),
+            Some(PathBuf::from("/var/log")),
+            Some(PathBuf::from("/srv/app")),
+        ]);
+    }
+
+    #[test]
+    fn needs_the_gem_only_for_what_the_launcher_lacks() {
+        assert!(!Project::parse(BLOG).unwrap().needs_gem());
+
+        let gem = Project::parse("socket_name: work\nwindows:\n  - servers:\n      synchronize: after\n      panes: [ssh a, ssh b]\n").unwrap();
+        assert!(gem.needs_gem());
+        assert_eq!(gem.gem_only, vec!["socket_name", "synchronize"]);
+
+        assert!(Project::parse("root: <%= @settings['dir'] %>\nwindows:\n  - main: vim\n").unwrap().needs_gem());
+    }
+
+    #[tokio::test]
+    async fn resolves_local_file_roots_against_its_directory() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().canonicalize().unwrap();
+        std::fs::create_dir(dir.join("web")).unwrap();
+        let file = dir.join(".tmuxinator.yml");
+
+        // The tests run from the crate directory, so `.` must not mean the current directory
+        std::fs::write(&file, "name: app\nroot: .\nwindows:\n  - web:\n      root: web\n").unwrap();
+        let project = Project::load(&file).await.unwrap();
+        assert_eq!(project.root_dir(), Some(dir.clone()));
+        assert_eq!(project.window_dir(&project.windows[0]), Some(dir.join("web")));
+        assert!(project.issues.is_empty(), "{:?}", project.issues);
+
+        std::fs::write(&file, "windows:\n  - shell:\n").unwrap();
+        let project = Project::load(&file).await.unwrap();
+        assert_eq!(project.root_dir(), Some(dir.clone()));
+        assert!(project.issues.is_empty(), "{:?}", project.issues);
+    }
+}
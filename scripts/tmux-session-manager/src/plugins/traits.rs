@@ -34,6 +34,13 @@ pub trait SessionPlugin: Send + Sync {
         tmux.kill_session(session_name).await
     }
 
+    /// Rename a running session, returning a warning for the user if something still refers to the old name
+    async fn rename(&self, old_name: &str, new_name: &str, _metadata: &SessionMetadata) -> Result<Option<String>> {
+        use crate::core::tmux::TmuxClient;
+        let tmux = TmuxClient::new();
+        tmux.rename_session(old_name, new_name).await?;
+        Ok(None)
+    }
 
     /// Start a session in background (for tmuxinator-like plugins)
     async fn start(&self, session_name: &str, metadata: &SessionMetadata) -> Result<()> {
@@ -41,6 +48,17 @@ pub trait SessionPlugin: Send + Sync {
         self.switch(session_name, metadata).await
     }
 
+    /// Windows a running session lacks compared to what the plugin would create for it,
+    /// such as those of a tmuxinator project; plugins without such a definition have none
+    async fn missing_windows(&self, _session_name: &str, _metadata: &SessionMetadata) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    /// Add the windows `missing_windows` reports to the running session, returning their names
+    async fn add_missing_windows(&self, _session_name: &str, _metadata: &SessionMetadata) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
     /// Get help text for this plugin's session types
     fn get_help_text(&self) -> Vec<String> {
         Vec::new() // Default: no plugin-specific help
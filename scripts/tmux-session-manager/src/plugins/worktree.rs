@@ -1,19 +1,91 @@
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use std::path::Path;
-use tokio::process::Command;
-
-use crate::core::{session::{SessionContext, SessionItem, SessionMetadata}, tmux::TmuxClient};
+use chrono::{DateTime, Utc};
+use std::path::{Path, PathBuf};
+
+use crate::config::WorktreeSettings;
+use crate::core::git_status::{self, StatusCache};
+use crate::core::session::{SessionContext, SessionItem, SessionMetadata, TmuxSession};
+use crate::core::{directories, git, naming};
+use crate::core::tmux::TmuxClient;
+use crate::plugins::checkout::{self, CheckoutSessions};
 use crate::plugins::SessionPlugin;
 
 pub struct WorktreePlugin {
     tmux: TmuxClient,
+    sessions: CheckoutSessions,
+    /// Repositories listed even when none of their worktrees has a session
+    repos: Vec<PathBuf>,
+    naming: String,
+    /// Reused when the picker list is redrawn within one interactive run. Previews run as
+    /// separate `--generate-preview` processes, so each reads its worktree afresh.
+    statuses: StatusCache,
+}
+
+/// The worktrees of one repository, named after its main checkout
+struct RepoWorktrees {
+    name: String,
+    worktrees: Vec<git::Worktree>,
+}
+
+impl RepoWorktrees {
+    fn contains(&self, dir: &Path) -> bool {
+        self.worktrees.iter().any(|worktree| dir.starts_with(&worktree.path))
+    }
+
+    /// Worktrees besides the main checkout, which git always lists first; deleted ones are left out
+    fn linked(&self) -> impl Iterator<Item = &git::Worktree> {
+        self.worktrees.iter().skip(1).filter(|worktree| !worktree.is_bare && !worktree.is_prunable)
+    }
+}
+
+/// Worktrees whose directory was deleted, and the sessions left behind in them
+#[derive(Debug, Default)]
+pub struct StaleWorktrees {
+    /// Main checkout and path of each worktree git reports as prunable
+    pub prunable: Vec<(PathBuf, PathBuf)>,
+    /// Session name and the deleted worktree directory it was in
+    pub orphaned_sessions: Vec<(String, String)>,
+}
+
+impl StaleWorktrees {
+    pub fn is_empty(&self) -> bool {
+        self.prunable.is_empty() && self.orphaned_sessions.is_empty()
+    }
+
+    /// One line per finding, for the doctor report and the cleanup confirmation
+    pub fn describe(&self) -> Vec<String> {
+        let worktrees = self
+            .prunable
+            .iter()
+            .map(|(_, path)| format!("prunable worktree: {}", directories::abbreviate_home(path)));
+        let sessions = self
+            .orphaned_sessions
+            .iter()
+            .map(|(session_name, dir)| format!("orphaned session: {} (in {})", session_name, dir));
+
+        worktrees.chain(sessions).collect()
+    }
+}
+
+impl Default for WorktreePlugin {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl WorktreePlugin {
     pub fn new() -> Self {
+        Self::with_settings(&WorktreeSettings::default())
+    }
+
+    pub fn with_settings(settings: &WorktreeSettings) -> Self {
         Self {
             tmux: TmuxClient::new(),
+            sessions: CheckoutSessions::new("worktree", "worktree"),
+            repos: settings.repos.iter().map(|repo| directories::expand_home(repo)).collect(),
+            naming: settings.naming.clone(),
+            statuses: StatusCache::new(),
         }
     }
 
@@ -22,97 +94,99 @@ impl WorktreePlugin {
         let current_dir = if let Some(current_session) = &context.current_session {
             self.tmux.get_session_path(current_session).await?
         } else {
-            Some(std::env::current_dir()?.to_string_lossy().to_string())
+            context.working_dir.as_ref().map(|dir| dir.to_string_lossy().to_string())
         };
 
-        if let Some(dir) = current_dir {
-            let output = Command::new("git")
-                .args(&["rev-parse", "--show-toplevel"])
-                .current_dir(&dir)
-                .output()
-                .await;
-
-            if let Ok(output) = output {
-                if output.status.success() {
-                    let root = String::from_utf8_lossy(&output.stdout)
-                        .trim()
-                        .to_string();
-                    return Ok(Some(root));
-                }
+        match current_dir {
+            // The container of a bare layout has no work tree, but its shared git dir still lists the worktrees
+            Some(dir) => {
+                let root = match git::repo_root(Path::new(&dir)).await {
+                    Some(root) => Some(root),
+                    None => git::common_dir(Path::new(&dir)).await,
+                };
+                Ok(root.map(|root| root.to_string_lossy().to_string()))
             }
+            None => Ok(None),
         }
+    }
 
-        Ok(None)
+    /// Worktrees of every configured repository, the current one and each one a session is open in
+    async fn collect_repos(&self, context: &SessionContext) -> Vec<RepoWorktrees> {
+        let current = self.get_current_repo_root(context).await.ok().flatten().map(PathBuf::from);
+        let dirs = CheckoutSessions::candidate_dirs(&self.repos, current, context);
+
+        // One `git worktree list` per checkout covers every session inside it
+        checkout::collect_repos(dirs, git::checkout_of, |dir| async move {
+            let worktrees = git::list_worktrees(&dir).await.ok()?;
+            let main = worktrees.first()?.path.clone();
+            let name = worktrees[0].repo_name();
+            Some((main, RepoWorktrees { name, worktrees }))
+        })
+        .await
     }
 
-    async fn list_worktrees(&self, repo_root: &str) -> Result<Vec<(String, String)>> {
-        let output = Command::new("git")
-            .args(&["worktree", "list", "--porcelain"])
-            .current_dir(repo_root)
-            .output()
-            .await?;
+    /// Worktrees git marks as prunable in every known repository, and sessions whose directory
+    /// was a worktree that no longer exists
+    pub async fn find_stale(&self, context: &SessionContext) -> StaleWorktrees {
+        let mut stale = StaleWorktrees::default();
 
-        if !output.status.success() {
-            return Err(anyhow!("Failed to list git worktrees"));
-        }
-
-        let output_str = String::from_utf8_lossy(&output.stdout);
-        let mut worktrees = Vec::new();
-        let mut current_path = None;
-        let mut current_branch = None;
-
-        for line in output_str.lines() {
-            if line.starts_with("worktree ") {
-                current_path = Some(line.strip_prefix("worktree ").unwrap().to_string());
-            } else if line.starts_with("branch ") {
-                let branch = line.strip_prefix("branch refs/heads/")
-                    .unwrap_or(line.strip_prefix("branch ").unwrap_or("unknown"));
-                current_branch = Some(branch.to_string());
+        for repo in self.collect_repos(context).await {
+            let main = repo.worktrees[0].path.clone();
+            for worktree in repo.worktrees.iter().filter(|worktree| worktree.is_prunable) {
+                stale.prunable.push((main.clone(), worktree.path.clone()));
             }
+        }
 
-            if let (Some(path), Some(_branch)) = (&current_path, &current_branch) {
-                let worktree_name = Path::new(path)
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("unknown")
-                    .to_string();
+        for session in &context.all_tmux_sessions {
+            // Linux reports the cwd of a shell whose directory was removed as "<path> (deleted)"
+            let missing_dir = [&session.current_path, &session.options.path]
+                .into_iter()
+                .flatten()
+                .map(|dir| dir.trim_end_matches(" (deleted)"))
+                .find(|dir| !Path::new(dir).exists());
+            let Some(dir) = missing_dir else {
+                continue;
+            };
 
-                worktrees.push((path.clone(), worktree_name));
-                current_path = None;
-                current_branch = None;
+            let was_worktree = session.options.owned_by(self.name()) == Some(true)
+                || stale.prunable.iter().any(|(_, path)| Path::new(dir).starts_with(path));
+            if was_worktree {
+                stale.orphaned_sessions.push((session.name.clone(), dir.to_string()));
             }
         }
 
-        Ok(worktrees)
+        stale
     }
 
-    async fn is_session_in_worktree(&self, session_name: &str) -> Result<bool> {
-        if let Some(session_path) = self.tmux.get_session_path(session_name).await? {
-            let git_file = Path::new(&session_path).join(".git");
-            if git_file.exists() {
-                // Check if .git is a file (worktree) or directory (regular repo)
-                if git_file.is_file() {
-                    match tokio::fs::read_to_string(&git_file).await {
-                        Ok(contents) => return Ok(contents.contains("gitdir:")),
-                        Err(_) => return Ok(false), // Can't read file, assume not a worktree
-                    }
-                } else {
-                    // .git is a directory, so this is a regular repo, not a worktree
-                    return Ok(false);
-                }
+    /// Run `git worktree prune` in each repository with prunable worktrees
+    pub async fn prune_stale(&self, stale: &StaleWorktrees) -> Result<()> {
+        let mut pruned: Vec<&PathBuf> = Vec::new();
+        for (main, _) in &stale.prunable {
+            if !pruned.contains(&main) {
+                git::prune_worktrees(main).await?;
+                pruned.push(main);
             }
         }
-        Ok(false)
+        Ok(())
     }
 
-    /// Generate a tmux-safe session name (tmux doesn't like names starting with dots)
-    fn get_tmux_safe_name(&self, session_name: &str) -> String {
-        if session_name.starts_with('.') {
-            session_name[1..].to_string() // Remove the leading dot
-        } else {
-            session_name.to_string()
+    /// Picker name and session identity of a worktree, from the naming pattern; the tmux
+    /// session name is this passed through the sanitizer
+    fn worktree_identity(&self, repo: &RepoWorktrees, worktree: &git::Worktree) -> String {
+        let worktree_name = worktree.name();
+        let branch = worktree.branch.clone().unwrap_or_else(|| worktree_name.clone());
+
+        naming::checkout_identity(&self.naming, &repo.name, &branch, &worktree_name)
+    }
+
+    /// Stamped sessions say exactly who owns them; older ones fall back to the .git file check
+    async fn is_worktree_session(&self, session: &TmuxSession, session_dir: &str) -> bool {
+        match session.options.owned_by(self.name()) {
+            Some(owned) => owned,
+            None => git::is_worktree_dir(Path::new(session_dir)).await,
         }
     }
+
 }
 
 #[async_trait]
@@ -135,17 +209,29 @@ impl SessionPlugin for WorktreePlugin {
 
     async fn discover(&self, context: &SessionContext) -> Result<Vec<SessionItem>> {
         let mut sessions = Vec::new();
+        let repos = self.collect_repos(context).await;
+        let repo_of = |dir: &str| {
+            repos
+                .iter()
+                .find(|repo| repo.contains(Path::new(dir)))
+                .map(|repo| repo.name.clone())
+        };
 
         // First, check existing tmux sessions for worktrees
-        let mut discovered_worktrees = Vec::new();
+        let mut discovered_paths = Vec::new();
         for tmux_session in &context.all_tmux_sessions {
-            if self.is_session_in_worktree(&tmux_session.name).await? {
+            let Some(session_dir) = CheckoutSessions::session_dir(tmux_session) else {
+                continue;
+            };
+
+            if self.is_worktree_session(tmux_session, &session_dir).await {
                 let is_current = context.current_session.as_ref() == Some(&tmux_session.name);
 
                 let metadata = SessionMetadata::new("worktree".to_string())
-                    .with_exists(true);
+                    .with_exists(true)
+                    .with_path(session_dir.clone());
 
-                let session_item = SessionItem::new(
+                let mut session_item = SessionItem::new(
                     tmux_session.name.clone(),
                     "worktree".to_string(),
                     self.priority(),
@@ -154,102 +240,92 @@ impl SessionPlugin for WorktreePlugin {
                   .with_active(true)
                   .with_timestamp(tmux_session.last_attached);
 
+                if let Some(repo_name) = repo_of(&session_dir) {
+                    session_item = session_item.with_group(repo_name);
+                }
+
                 sessions.push(session_item);
-                discovered_worktrees.push(tmux_session.name.clone());
+                discovered_paths.push(PathBuf::from(session_dir));
             }
         }
 
-        // Then discover worktrees that don't have sessions yet
-        if let Ok(Some(repo_root)) = self.get_current_repo_root(context).await {
-            if let Ok(worktrees) = self.list_worktrees(&repo_root).await {
-                for (worktree_path, worktree_name) in worktrees {
-                    // Check both the clean name and tmux-safe name for existing sessions
-                    let tmux_safe_name = self.get_tmux_safe_name(&worktree_name);
-                    if discovered_worktrees.contains(&worktree_name) ||
-                       discovered_worktrees.contains(&tmux_safe_name) {
-                        continue;
-                    }
+        // Then the worktrees of every known repository that don't have sessions yet
+        for repo in &repos {
+            for worktree in repo.linked() {
+                let identity = self.worktree_identity(repo, worktree);
 
-                    // Skip the main repo (check if .git is a directory, not a file)
-                    let git_path = Path::new(&worktree_path).join(".git");
-                    if git_path.is_dir() {
-                        continue; // This is the main repo, not a worktree
-                    }
+                // Sessions map back through their identity, or the worktree path they belong to
+                if naming::find_session(&identity, &context.all_tmux_sessions).is_some() ||
+                   discovered_paths.contains(&worktree.path) {
+                    continue;
+                }
 
-                    let metadata = SessionMetadata::new("worktree".to_string())
-                        .with_exists(false)
-                        .with_path(worktree_path);
+                let metadata = SessionMetadata::new("worktree".to_string())
+                    .with_exists(false)
+                    .with_path(worktree.path.to_string_lossy().to_string());
 
-                    // Use the clean worktree name for display, not the tmux-safe name
-                    let session_item = SessionItem::new(
-                        worktree_name,
-                        "worktree".to_string(),
-                        self.priority(),
-                        metadata,
-                    ).with_active(false);
+                // Display the identity; the tmux name is only chosen when the session is created.
+                // Unstarted worktrees follow their repository's running sessions.
+                let session_item = SessionItem::new(
+                    identity,
+                    "worktree".to_string(),
+                    self.priority(),
+                    metadata,
+                ).with_active(false)
+                  .with_group(repo.name.clone())
+                  .with_timestamp(DateTime::<Utc>::UNIX_EPOCH);
 
-                    sessions.push(session_item);
-                }
+                sessions.push(session_item);
             }
         }
 
-        Ok(sessions)
+        // Every checkout is read at once, and reused from the cache when the list is redrawn
+        let paths: Vec<PathBuf> = sessions
+            .iter()
+            .filter_map(|session| session.metadata.path.as_ref().map(PathBuf::from))
+            .collect();
+        let statuses = self.statuses.statuses(&paths).await;
+
+        Ok(sessions
+            .into_iter()
+            .map(|session| {
+                let status = session.metadata.path.as_ref().and_then(|path| statuses.get(Path::new(path)));
+                match status {
+                    Some(status) => session.with_status(status.badges()),
+                    None => session,
+                }
+            })
+            .collect())
     }
 
     async fn resolve(&self, session_name: &str, context: &SessionContext) -> Result<SessionMetadata> {
-        // Check if session already exists (try both display name and tmux-safe name)
-        let tmux_safe_name = self.get_tmux_safe_name(session_name);
-        let session_exists = self.tmux.has_session(session_name).await ||
-                            self.tmux.has_session(&tmux_safe_name).await;
-
-        if session_exists {
-            // Try to get session path using either display name or tmux-safe name
-            let session_path = match self.tmux.get_session_path(session_name).await? {
-                Some(path) => Some(path),
-                None => self.tmux.get_session_path(&tmux_safe_name).await.ok().flatten(),
-            };
-
-            if let Some(session_path) = session_path {
-                let git_file = Path::new(&session_path).join(".git");
-                if git_file.exists() {
-                    let contents = tokio::fs::read_to_string(&git_file).await?;
-                    if contents.contains("gitdir:") {
-                        let mut metadata = SessionMetadata::new("worktree".to_string())
-                            .with_exists(true)
-                            .with_path(session_path.clone());
-
-                        // Get branch info
-                        let branch_output = Command::new("git")
-                            .args(&["branch", "--show-current"])
-                            .current_dir(&session_path)
-                            .output()
-                            .await;
-
-                        if let Ok(output) = branch_output {
-                            if output.status.success() {
-                                let branch = String::from_utf8_lossy(&output.stdout)
-                                    .trim()
-                                    .to_string();
-                                metadata = metadata.with_property("branch".to_string(), branch);
-                            }
-                        }
-
-                        return Ok(metadata);
+        if let Some(running_session) = CheckoutSessions::find_session(session_name, &context.all_tmux_sessions) {
+            if let Some(session_path) = CheckoutSessions::session_dir(running_session) {
+                if self.is_worktree_session(running_session, &session_path).await {
+                    let mut metadata = SessionMetadata::new("worktree".to_string())
+                        .with_exists(true)
+                        .with_path(session_path.clone());
+
+                    if let Some(branch) = self
+                        .statuses
+                        .status(Path::new(&session_path))
+                        .await
+                        .and_then(|status| status.branch)
+                    {
+                        metadata = metadata.with_property("branch".to_string(), branch);
                     }
+
+                    return Ok(metadata);
                 }
             }
         }
 
         // Session doesn't exist, try to find the worktree path
-        if let Ok(Some(repo_root)) = self.get_current_repo_root(context).await {
-            if let Ok(worktrees) = self.list_worktrees(&repo_root).await {
-                for (worktree_path, worktree_name) in worktrees {
-                    if worktree_name == session_name {
-                        return Ok(SessionMetadata::new("worktree".to_string())
-                            .with_exists(false)
-                            .with_path(worktree_path));
-                    }
-                }
+        for repo in self.collect_repos(context).await {
+            if let Some(worktree) = repo.linked().find(|worktree| self.worktree_identity(&repo, worktree) == session_name) {
+                return Ok(SessionMetadata::new("worktree".to_string())
+                    .with_exists(false)
+                    .with_path(worktree.path.to_string_lossy().to_string()));
             }
         }
 
@@ -257,33 +333,17 @@ impl SessionPlugin for WorktreePlugin {
     }
 
     async fn switch(&self, session_name: &str, metadata: &SessionMetadata) -> Result<()> {
-        let worktree_path = metadata.path.as_ref()
-            .ok_or_else(|| anyhow!("No worktree path found for session: {}", session_name))?;
-
-        if !Path::new(worktree_path).exists() {
-            return Err(anyhow!("Worktree path does not exist: {}", worktree_path));
-        }
-
-        // Use tmux-safe name for actual tmux operations
-        let tmux_session_name = self.get_tmux_safe_name(session_name);
+        let tmux_session_name = self.sessions.ensure_session(session_name, metadata).await?;
+        self.tmux.switch_or_attach(&tmux_session_name).await
+    }
 
-        if self.tmux.has_session(&tmux_session_name).await {
-            // Session exists, just switch
-            if TmuxClient::is_inside_tmux() {
-                self.tmux.switch_client(&tmux_session_name).await
-            } else {
-                self.tmux.attach_session(&tmux_session_name).await
-            }
-        } else {
-            // Create new session in worktree directory with tmux-safe name
-            self.tmux.new_session(&tmux_session_name, Some(worktree_path)).await?;
+    async fn start(&self, session_name: &str, metadata: &SessionMetadata) -> Result<()> {
+        // Create the session in the background without switching to it
+        self.sessions.ensure_session(session_name, metadata).await.map(|_| ())
+    }
 
-            if TmuxClient::is_inside_tmux() {
-                self.tmux.switch_client(&tmux_session_name).await
-            } else {
-                self.tmux.attach_session(&tmux_session_name).await
-            }
-        }
+    async fn rename(&self, old_name: &str, new_name: &str, metadata: &SessionMetadata) -> Result<Option<String>> {
+        self.sessions.rename(old_name, new_name, metadata).await
     }
 
     async fn preview(&self, session_name: &str, metadata: &SessionMetadata) -> Result<String> {
@@ -293,57 +353,18 @@ impl SessionPlugin for WorktreePlugin {
             preview.push_str(&format!("\x1b[1;33mWorktree path:\x1b[0m\n{}\n\n", worktree_path));
 
             if Path::new(worktree_path).exists() {
-                // Get branch info
-                let branch_output = Command::new("git")
-                    .args(&["branch", "--show-current"])
-                    .current_dir(worktree_path)
-                    .output()
-                    .await;
-
-                if let Ok(output) = branch_output {
-                    if output.status.success() {
-                        let branch = String::from_utf8_lossy(&output.stdout).trim().to_string();
-                        preview.push_str(&format!("\x1b[1;33mBranch info:\x1b[0m\n{}\n\n", branch));
-                    }
-                }
-
-                // Get recent commits
-                let log_output = Command::new("git")
-                    .args(&["log", "--oneline", "-5"])
-                    .current_dir(worktree_path)
-                    .output()
-                    .await;
-
-                if let Ok(output) = log_output {
-                    if output.status.success() {
-                        let commits = String::from_utf8_lossy(&output.stdout);
-                        preview.push_str("\x1b[1;33mRecent commits:\x1b[0m\n");
-                        for line in commits.lines() {
-                            preview.push_str(&format!("  {}\n", line));
-                        }
+                match self.statuses.status(Path::new(worktree_path)).await {
+                    Some(status) => {
+                        preview.push_str(&status.describe());
                         preview.push('\n');
                     }
+                    None => preview.push_str("\x1b[0;31mCould not read git status\x1b[0m\n\n"),
                 }
 
-                // Get working directory status
-                let status_output = Command::new("git")
-                    .args(&["status", "--porcelain"])
-                    .current_dir(worktree_path)
-                    .output()
-                    .await;
-
-                if let Ok(output) = status_output {
-                    if output.status.success() {
-                        let status = String::from_utf8_lossy(&output.stdout);
-                        preview.push_str("\x1b[1;33mWorking directory status:\x1b[0m\n");
-                        if status.is_empty() {
-                            preview.push_str("  Clean working directory\n");
-                        } else {
-                            for (i, line) in status.lines().enumerate() {
-                                if i >= 10 { break; } // Limit to first 10 files
-                                preview.push_str(&format!("  {}\n", line));
-                            }
-                        }
+                if let Ok(commits) = git_status::recent_commits(Path::new(worktree_path), 5) {
+                    preview.push_str("\x1b[1;33mRecent commits:\x1b[0m\n");
+                    for commit in commits {
+                        preview.push_str(&format!("  {}\n", commit));
                     }
                 }
             } else {
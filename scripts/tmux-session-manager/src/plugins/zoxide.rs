@@ -0,0 +1,144 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use std::path::PathBuf;
+use tokio::sync::OnceCell;
+
+use crate::core::directories;
+use crate::core::session::{SessionContext, SessionItem, SessionMetadata};
+use crate::core::tmux::TmuxClient;
+use crate::plugins::SessionPlugin;
+
+pub struct ZoxidePlugin {
+    tmux: TmuxClient,
+    /// Queried once per run; the picker loop and previews reuse the result
+    directories: OnceCell<Vec<(f64, PathBuf)>>,
+}
+
+impl Default for ZoxidePlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ZoxidePlugin {
+    pub fn new() -> Self {
+        Self {
+            tmux: TmuxClient::new(),
+            directories: OnceCell::new(),
+        }
+    }
+
+    /// Existing directories zoxide knows, highest score first. Paths with whitespace are left
+    /// out since picker entries are parsed back by whitespace-separated token.
+    async fn directories(&self) -> &[(f64, PathBuf)] {
+        self.directories
+            .get_or_init(|| async {
+                directories::zoxide_scores()
+                    .await
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|(_, dir)| !dir.to_string_lossy().contains(char::is_whitespace) && dir.is_dir())
+                    .collect()
+            })
+            .await
+    }
+
+    /// The directory behind a picker entry, which shows it with `~` abbreviated
+    async fn find_directory(&self, session_name: &str) -> Option<&(f64, PathBuf)> {
+        let dir = directories::expand_home(session_name);
+        self.directories().await.iter().find(|(_, known)| *known == dir)
+    }
+
+    /// Same rules as the open command: reuse the session at the repo root, else create one there
+    async fn ensure_session(&self, session_name: &str, metadata: &SessionMetadata) -> Result<String> {
+        let path = metadata
+            .path
+            .as_deref()
+            .ok_or_else(|| anyhow!("Directory unknown: {}", session_name))?;
+
+        let context = self.tmux.get_session_context().await.unwrap_or_default();
+        directories::ensure_session_for_path(&self.tmux, path.as_ref(), &context).await
+    }
+}
+
+#[async_trait]
+impl SessionPlugin for ZoxidePlugin {
+    fn name(&self) -> &str {
+        "zoxide"
+    }
+
+    fn description(&self) -> &str {
+        "Frecent directories from zoxide"
+    }
+
+    fn priority(&self) -> u32 {
+        70
+    }
+
+    fn dependencies(&self) -> Vec<&str> {
+        vec!["zoxide"]
+    }
+
+    async fn discover(&self, context: &SessionContext) -> Result<Vec<SessionItem>> {
+        let mut sessions = Vec::new();
+
+        for (score, dir) in self.directories().await {
+            // Directories with a running session are already listed through it
+            if directories::find_session_for_dir(dir, &context.all_tmux_sessions).is_some() {
+                continue;
+            }
+
+            let metadata = SessionMetadata::new("zoxide".to_string())
+                .with_exists(false)
+                .with_path(dir.to_string_lossy().to_string())
+                .with_property("score".to_string(), format!("{:.1}", score));
+
+            sessions.push(SessionItem::new(
+                directories::abbreviate_home(dir),
+                "zoxide".to_string(),
+                self.priority(),
+                metadata,
+            ).with_score(*score));
+        }
+
+        Ok(sessions)
+    }
+
+    async fn resolve(&self, session_name: &str, _context: &SessionContext) -> Result<SessionMetadata> {
+        let (score, dir) = self
+            .find_directory(session_name)
+            .await
+            .ok_or_else(|| anyhow!("Directory not known to zoxide: {}", session_name))?;
+
+        Ok(SessionMetadata::new("zoxide".to_string())
+            .with_exists(false)
+            .with_path(dir.to_string_lossy().to_string())
+            .with_property("score".to_string(), format!("{:.1}", score)))
+    }
+
+    async fn switch(&self, session_name: &str, metadata: &SessionMetadata) -> Result<()> {
+        let tmux_session_name = self.ensure_session(session_name, metadata).await?;
+        self.tmux.switch_or_attach(&tmux_session_name).await
+    }
+
+    async fn start(&self, session_name: &str, metadata: &SessionMetadata) -> Result<()> {
+        self.ensure_session(session_name, metadata).await.map(|_| ())
+    }
+
+    async fn preview(&self, session_name: &str, metadata: &SessionMetadata) -> Result<String> {
+        let mut preview = format!("\x1b[0;33m○ Directory: {}\x1b[0m\n\n", session_name);
+
+        if let Some(score) = metadata.get_property("score") {
+            preview.push_str(&format!("\x1b[1;33mzoxide score:\x1b[0m {}\n", score));
+        }
+        preview.push_str("\n\x1b[1;33mSession will be created when selected\x1b[0m\n");
+
+        Ok(preview)
+    }
+
+    fn get_help_text(&self) -> Vec<String> {
+        vec![
+            "\x1b[0;33m○\x1b[0m - zoxide directory (no session yet)".to_string(),
+        ]
+    }
+}
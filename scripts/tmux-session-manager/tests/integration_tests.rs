@@ -1,3 +1,6 @@
+// Lints the original tests predate; kept as written
+#![allow(clippy::single_component_path_imports, clippy::unnecessary_sort_by, clippy::len_zero, clippy::bool_assert_comparison)]
+
 use anyhow::Result;
 use chrono::{TimeZone, Utc};
 use std::collections::HashMap;
@@ -26,6 +29,7 @@ impl MockTmuxClient {
             windows: 1,
             attached: false,
             current_path: Some(format!("/Users/test/{}", name)),
+            options: SessionOptions::default(),
         };
         self.sessions.insert(name, tmux_session);
         self
@@ -443,3 +447,376 @@ async fn test_worktree_session_switching() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_pinned_and_tagged_session_formatting() {
+    let session = SessionItem::new(
+        "api".to_string(),
+        "active".to_string(),
+        10,
+        SessionMetadata::new("active".to_string()).with_exists(true),
+    )
+    .with_pinned(true)
+    .with_tags(vec!["work".to_string()]);
+
+    let formatted = session.format_for_display();
+    let tokens: Vec<&str> = formatted.split_whitespace().collect();
+
+    // The session name must stay the second token so selections can be parsed back
+    assert_eq!(tokens[1], "api");
+    assert!(formatted.contains("\u{f435}"));
+    assert!(formatted.contains("#work"));
+}
+
+#[tokio::test]
+async fn test_new_session_name_validation() {
+    let existing = TmuxSession {
+        name: "api".to_string(),
+        last_attached: Utc::now(),
+        windows: 1,
+        attached: false,
+        current_path: None,
+        options: SessionOptions::default(),
+    };
+    let context = SessionContext::new().with_all_tmux_sessions(vec![existing]);
+    let manager = PluginManager::new();
+
+    assert!(manager.validate_new_name("api-v2", &context).is_ok());
+    assert!(manager.validate_new_name("feature/login", &context).is_ok());
+
+    // tmux target separators and collisions are rejected before touching tmux
+    assert!(manager.validate_new_name("v1.2", &context).is_err());
+    assert!(manager.validate_new_name("host:8080", &context).is_err());
+    assert!(manager.validate_new_name("  ", &context).is_err());
+
+    let collision = manager.validate_new_name("api", &context).unwrap_err();
+    assert_eq!(collision.to_string(), "Session 'api' already exists");
+}
+
+#[tokio::test]
+async fn test_configured_projects_are_always_listed() -> Result<()> {
+    let project = |name: &str, path: &str| -> config::ProjectEntry {
+        serde_json::from_value(serde_json::json!({ "name": name, "path": path, "tags": ["work"] })).unwrap()
+    };
+    let plugin_manager = PluginManager::new()
+        .with_projects(vec![project("api", "/Users/test/api"), project("web.app", "/Users/test/web")], Vec::new());
+
+    let mut mock_client = MockTmuxClient::new();
+    mock_client
+        .add_session("main".to_string(), 1234567890) // Most recent, claimed by the recent plugin
+        .add_session("api".to_string(), 1234567889);
+    let context = mock_client.build_context();
+    let sessions = plugin_manager.discover_all_sessions(&context).await?;
+
+    // The running project replaces its plain active entry
+    let api: Vec<_> = sessions.iter().filter(|s| s.name == "api").collect();
+    assert_eq!(api.len(), 1);
+    assert_eq!(api[0].plugin_name, "project");
+    assert!(api[0].is_active);
+
+    // A project without a session is still offered, under its unsanitized name
+    let web = sessions.iter().find(|s| s.name == "web.app").expect("project should be listed");
+    assert!(!web.is_active);
+    assert_eq!(web.tags, vec!["work".to_string()]);
+    assert_eq!(web.metadata.path.as_deref(), Some("/Users/test/web"));
+
+    Ok(())
+}
+
+fn git(dir: &std::path::Path, args: &[&str]) {
+    let status = std::process::Command::new("git")
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .expect("git should run")
+        .status;
+    assert!(status.success(), "git {:?} failed", args);
+}
+
+/// A repository with one commit and a linked worktree `<name>-worktrees/<branch>` next to it
+fn repo_with_worktree(root: &std::path::Path, name: &str, branch: &str) -> std::path::PathBuf {
+    let repo = root.join(name);
+    std::fs::create_dir_all(&repo).unwrap();
+    git(&repo, &["init", "-q"]);
+    git(&repo, &["commit", "-q", "--allow-empty", "-m", "init"]);
+
+    let worktree = root.join(format!("{}-worktrees", name)).join(branch);
+    git(&repo, &["worktree", "add", "-q", "-b", branch, worktree.to_str().unwrap()]);
+    worktree
+}
+
+#[tokio::test]
+async fn test_worktrees_listed_for_every_repository() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    let root_path = root.path().canonicalize()?;
+    repo_with_worktree(&root_path, "api", "login");
+    repo_with_worktree(&root_path, "web", "theme");
+    repo_with_worktree(&root_path, "docs", "draft");
+
+    // A session is open in api, and web is configured; docs is neither, and the manager was
+    // started outside any repository
+    let mut mock_client = MockTmuxClient::new();
+    mock_client.add_session("api".to_string(), 1234567890);
+    let mut context = mock_client.build_context().with_working_dir(Some(root_path.clone()));
+    context.all_tmux_sessions[0].current_path = Some(root_path.join("api").display().to_string());
+
+    let settings = config::WorktreeSettings {
+        repos: vec![root_path.join("web").display().to_string()],
+        ..Default::default()
+    };
+    let plugin_manager = PluginManager::new().with_worktree_settings(&settings);
+    let sessions = plugin_manager.discover_all_sessions(&context).await?;
+
+    let worktrees: Vec<(&str, Option<&str>)> = sessions
+        .iter()
+        .filter(|s| s.plugin_name == "worktree")
+        .map(|s| (s.name.as_str(), s.group.as_deref()))
+        .collect();
+
+    // Grouped by repository, and unaffected by which session is current
+    assert_eq!(worktrees, vec![("api/login", Some("api")), ("web/theme", Some("web"))]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_worktree_names_are_qualified_by_repository() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    let root_path = root.path().canonicalize()?;
+    repo_with_worktree(&root_path, "api", "feature");
+    let web_feature = repo_with_worktree(&root_path, "web", "feature");
+
+    let settings = config::WorktreeSettings {
+        repos: vec![root_path.join("api").display().to_string(), root_path.join("web").display().to_string()],
+        naming: "{repo}-{worktree}".to_string(),
+        ..Default::default()
+    };
+    let plugin_manager = PluginManager::new().with_worktree_settings(&settings);
+    let context = SessionContext::new();
+    let sessions = plugin_manager.discover_all_sessions(&context).await?;
+
+    // Same worktree directory name in both repositories, yet neither entry is deduplicated away
+    let names: Vec<&str> = sessions.iter().filter(|s| s.plugin_name == "worktree").map(|s| s.name.as_str()).collect();
+    assert_eq!(names, vec!["api-feature", "web-feature"]);
+
+    let plugin = plugin_manager.find_plugin_for_session("web-feature", &context).await.expect("worktree plugin");
+    let metadata = plugin.resolve("web-feature", &context).await?;
+    assert_eq!(plugin.name(), "worktree");
+    assert_eq!(metadata.path, Some(web_feature.display().to_string()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_worktree_status_reports_work_that_removal_would_lose() -> Result<()> {
+    use tmux_session_manager::core::git;
+
+    let root = tempfile::tempdir()?;
+    let root_path = root.path().canonicalize()?;
+    let worktree = repo_with_worktree(&root_path, "api", "login");
+    let repo = root_path.join("api");
+    assert!(git::worktree_status(&worktree).await?.is_clean());
+
+    // A stash, then a leftover change; without remotes no commit counts as unpushed
+    std::fs::write(worktree.join("notes.txt"), "draft")?;
+    git(&worktree, &["stash", "-q", "-u"]);
+    std::fs::write(worktree.join("notes.txt"), "draft")?;
+    let status = git::worktree_status(&worktree).await?;
+    assert_eq!(status.uncommitted, vec!["?? notes.txt"]);
+    assert_eq!(status.stashes.len(), 1);
+    assert!(status.unpushed.is_empty());
+
+    // Dirty worktrees are only removed when forced; the branch has nothing main lacks
+    assert!(git::remove_worktree(&repo, &worktree, false).await.is_err());
+    git::remove_worktree(&repo, &worktree, true).await?;
+    assert!(!worktree.exists());
+    assert!(git::is_merged(&repo, "login", "HEAD").await);
+    git::delete_branch(&repo, "login").await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_deleted_worktrees_are_reported_and_pruned() -> Result<()> {
+    use tmux_session_manager::core::git;
+    use tmux_session_manager::plugins::worktree::WorktreePlugin;
+
+    let root = tempfile::tempdir()?;
+    let root_path = root.path().canonicalize()?;
+    let worktree = repo_with_worktree(&root_path, "api", "login");
+    let repo = root_path.join("api");
+    std::fs::remove_dir_all(&worktree)?;
+
+    // One session was left in the deleted worktree, the other is still in the main checkout
+    let mut mock_client = MockTmuxClient::new();
+    mock_client.add_session("login".to_string(), 1234567890);
+    mock_client.add_session("api".to_string(), 1234567800);
+    let mut context = mock_client.build_context();
+    context.all_tmux_sessions[0].current_path = Some(format!("{} (deleted)", worktree.display()));
+    context.all_tmux_sessions[1].current_path = Some(repo.display().to_string());
+
+    let plugin = WorktreePlugin::with_settings(&config::WorktreeSettings::default());
+    let stale = plugin.find_stale(&context).await;
+    assert_eq!(stale.prunable, vec![(repo.clone(), worktree.clone())]);
+    assert_eq!(stale.orphaned_sessions, vec![("login".to_string(), worktree.display().to_string())]);
+
+    // Deleted worktrees are not offered in the picker
+    let sessions = plugin.discover(&context).await?;
+    assert!(sessions.iter().all(|s| s.name != "api/login"));
+
+    plugin.prune_stale(&stale).await?;
+    assert_eq!(git::list_worktrees(&repo).await?.len(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_bare_repository_layout_lists_every_worktree() -> Result<()> {
+    use tmux_session_manager::core::git;
+
+    // app/.bare holds the repository, app/.git points at it and each branch is a sibling worktree
+    let root = tempfile::tempdir()?;
+    let root_path = root.path().canonicalize()?;
+    let source = root_path.join("source");
+    std::fs::create_dir_all(&source)?;
+    git(&source, &["init", "-q", "-b", "main"]);
+    git(&source, &["commit", "-q", "--allow-empty", "-m", "init"]);
+    let app = root_path.join("app");
+    std::fs::create_dir_all(&app)?;
+    git(&root_path, &["clone", "-q", "--bare", source.to_str().unwrap(), "app/.bare"]);
+    std::fs::write(app.join(".git"), "gitdir: ./.bare\n")?;
+    git(&app, &["worktree", "add", "-q", "main"]);
+    git(&app, &["worktree", "add", "-q", "-b", "feature", "feature"]);
+
+    assert!(!git::is_worktree_dir(&app).await);
+    assert!(git::is_worktree_dir(&app.join("main")).await);
+
+    // A session in the container directory is enough to find the repository
+    let mut mock_client = MockTmuxClient::new();
+    mock_client.add_session("app".to_string(), 1234567890);
+    let mut context = mock_client.build_context();
+    context.all_tmux_sessions[0].current_path = Some(app.display().to_string());
+
+    let plugin_manager = PluginManager::new();
+    let sessions = plugin_manager.discover_all_sessions(&context).await?;
+    let worktrees: Vec<(&str, Option<&str>)> = sessions
+        .iter()
+        .filter(|s| s.plugin_name == "worktree")
+        .map(|s| (s.name.as_str(), s.group.as_deref()))
+        .collect();
+
+    assert_eq!(worktrees, vec![("app/feature", Some("app")), ("app/main", Some("app"))]);
+    assert!(sessions.iter().any(|s| s.name == "app" && s.plugin_name != "worktree"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_worktree_entries_show_git_status_badges() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    let root_path = root.path().canonicalize()?;
+    let worktree = repo_with_worktree(&root_path, "api", "login");
+    std::fs::write(worktree.join("notes.txt"), "draft")?;
+
+    let settings = config::WorktreeSettings {
+        repos: vec![root_path.join("api").display().to_string()],
+        ..Default::default()
+    };
+    let plugin_manager = PluginManager::new().with_worktree_settings(&settings);
+    let sessions = plugin_manager.discover_all_sessions(&SessionContext::new()).await?;
+
+    let login = sessions.iter().find(|s| s.name == "api/login").expect("worktree entry");
+    let status = login.status.as_deref().expect("status badges");
+    assert!(status.contains("?1"));
+    assert!(login.format_for_display().contains(status));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_jj_workspaces_are_listed_and_resolved() -> Result<()> {
+    use tmux_session_manager::plugins::jj::JjPlugin;
+
+    let jj_installed = std::process::Command::new("jj").arg("--version").output().is_ok_and(|output| output.status.success());
+    if !jj_installed {
+        return Ok(());
+    }
+
+    let root = tempfile::tempdir()?;
+    let root_path = root.path().canonicalize()?;
+    // An empty config keeps the user's own jj settings out of the test
+    let jj_config = root_path.join("jj-config.toml");
+    std::fs::write(&jj_config, "")?;
+    let jj = |dir: &std::path::Path, args: &[&str]| {
+        let status = std::process::Command::new("jj")
+            .args(args)
+            .current_dir(dir)
+            .env("JJ_CONFIG", &jj_config)
+            .env("JJ_USER", "Test")
+            .env("JJ_EMAIL", "test@example.com")
+            .status()
+            .unwrap();
+        assert!(status.success(), "jj {:?} failed", args);
+    };
+    let repo = root_path.join("app");
+    std::fs::create_dir_all(&repo)?;
+    jj(&repo, &["git", "init"]);
+    jj(&repo, &["workspace", "add", "--name", "feature", "../feature"]);
+
+    let settings = config::WorktreeSettings {
+        repos: vec![repo.display().to_string()],
+        ..Default::default()
+    };
+    let plugin = JjPlugin::with_settings(&settings);
+    // Started outside any repository, so only the configured one is listed
+    let context = SessionContext::new().with_working_dir(Some(root_path.clone()));
+
+    // The default workspace stays a plain session; the other one is listed under its repository
+    let sessions = plugin.discover(&context).await?;
+    let workspaces: Vec<(&str, Option<&str>)> = sessions.iter().map(|s| (s.name.as_str(), s.group.as_deref())).collect();
+    assert_eq!(workspaces, vec![("app/feature", Some("app"))]);
+
+    let metadata = plugin.resolve("app/feature", &context).await?;
+    assert!(!metadata.exists);
+    assert_eq!(metadata.path, Some(root_path.join("feature").display().to_string()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_project_local_tmuxinator_files_are_found_and_named_by_their_name_key() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    let root_path = root.path().canonicalize()?;
+
+    // A configured repository, and one only known through a session open in a subdirectory
+    let configured = root_path.join("configured");
+    let opened = root_path.join("opened");
+    std::fs::create_dir_all(configured.join(".git"))?;
+    std::fs::create_dir_all(opened.join(".git"))?;
+    std::fs::create_dir_all(opened.join("src/deep"))?;
+    std::fs::write(configured.join(".tmuxinator.yml"), "name: blog-local\nwindows:\n  - editor: vim\n")?;
+    std::fs::write(opened.join(".tmuxinator.yml"), "windows:\n  - server: make serve\n")?;
+
+    let mut mock_client = MockTmuxClient::new();
+    mock_client.add_session("work".to_string(), 1234567890);
+    let mut context = mock_client.build_context();
+    context.all_tmux_sessions[0].current_path = Some(opened.join("src/deep").display().to_string());
+
+    let plugin_manager = PluginManager::new()
+        .with_tmuxinator_project_dirs(&[configured.display().to_string()]);
+    let sessions = plugin_manager.discover_all_sessions(&context).await?;
+    let projects: Vec<(&str, Option<&str>)> = sessions
+        .iter()
+        .filter(|s| s.plugin_name == "tmuxinator")
+        .map(|s| (s.name.as_str(), s.metadata.get_property("config_path").map(String::as_str)))
+        .collect();
+
+    let configured_file = configured.join(".tmuxinator.yml").display().to_string();
+    let opened_file = opened.join(".tmuxinator.yml").display().to_string();
+    assert!(projects.contains(&("blog-local", Some(configured_file.as_str()))), "{:?}", projects);
+    // Without a `name:` the project is named after its directory
+    assert!(projects.contains(&("opened", Some(opened_file.as_str()))), "{:?}", projects);
+
+    Ok(())
+}